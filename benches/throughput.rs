@@ -0,0 +1,95 @@
+use circular_buffer::CircularBuffer;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+// NOTE: run with `cargo bench`, every bulk benchmark runs once with the elements stored contiguously and once with
+// the elements wrapping around the end of the internal buffer, which is the slow path for most bulk operations
+
+const CAPACITY: usize = 8192;
+const SIZES: [usize; 3] = [16, 256, 4096];
+
+// returns an empty buffer whose head (and tail) is at the given offset in the internal buffer
+fn positioned(offset: usize) -> CircularBuffer<u64> {
+    let mut buf = CircularBuffer::new(CAPACITY);
+    buf.write_many(&vec![0; offset]).unwrap();
+    buf.read_many(offset).unwrap();
+    buf
+}
+
+// returns the offset at which `size` elements are either stored contiguously, or split in two equal halves
+fn offsets(size: usize) -> [(&'static str, usize); 2] {
+    [("contiguous", 0), ("wrapping", CAPACITY - size / 2)]
+}
+
+fn single_elements(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single");
+    group.throughput(Throughput::Elements(1));
+
+    // the buffer stays half full, so the head and tail keep wrapping around
+    let mut buf = positioned(0);
+    buf.write_many(&vec![0; CAPACITY / 2]).unwrap();
+    group.bench_function("write_read", |b| b.iter(|| {
+        buf.write(black_box(1)).unwrap();
+        black_box(buf.read().unwrap())
+    }));
+
+    group.bench_function("peek", |b| b.iter(|| black_box(buf.peek().unwrap())));
+    group.finish();
+}
+
+fn bulk_writes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk_write");
+    for &size in SIZES.iter() {
+        let values = vec![1u64; size];
+        group.throughput(Throughput::Elements(size as u64));
+        for &(layout, offset) in offsets(size).iter() {
+            group.bench_with_input(BenchmarkId::new(format!("write_many/{}", layout), size), &values, |b, values| {
+                b.iter_batched_ref(|| positioned(offset), |buf| buf.write_many(values).unwrap(), BatchSize::SmallInput)
+            });
+            group.bench_with_input(BenchmarkId::new(format!("write_slice/{}", layout), size), &values, |b, values| {
+                b.iter_batched_ref(|| positioned(offset), |buf| buf.write_slice(values).unwrap(), BatchSize::SmallInput)
+            });
+            group.bench_with_input(BenchmarkId::new(format!("write/{}", layout), size), &values, |b, values| {
+                b.iter_batched_ref(|| positioned(offset), |buf| {
+                    for &value in values {
+                        buf.write(value).unwrap();
+                    }
+                }, BatchSize::SmallInput)
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bulk_reads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk_read");
+    for &size in SIZES.iter() {
+        group.throughput(Throughput::Elements(size as u64));
+        for &(layout, offset) in offsets(size).iter() {
+            let filled = || {
+                let mut buf = positioned(offset);
+                buf.write_many(&vec![1; size]).unwrap();
+                buf
+            };
+            group.bench_function(BenchmarkId::new(format!("read_many/{}", layout), size), |b| {
+                b.iter_batched_ref(filled, |buf| buf.read_many(size).unwrap(), BatchSize::SmallInput)
+            });
+            group.bench_function(BenchmarkId::new(format!("read_slice/{}", layout), size), |b| {
+                b.iter_batched_ref(filled, |buf| buf.read_slice(size).unwrap(), BatchSize::SmallInput)
+            });
+            group.bench_function(BenchmarkId::new(format!("read/{}", layout), size), |b| {
+                b.iter_batched_ref(filled, |buf| {
+                    for _ in 0..size {
+                        black_box(buf.read().unwrap());
+                    }
+                }, BatchSize::SmallInput)
+            });
+            group.bench_function(BenchmarkId::new(format!("peek_many/{}", layout), size), |b| {
+                b.iter_batched_ref(filled, |buf| buf.peek_many(size).unwrap(), BatchSize::SmallInput)
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, single_elements, bulk_writes, bulk_reads);
+criterion_main!(benches);
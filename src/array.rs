@@ -0,0 +1,177 @@
+use crate::CircularBuffer;
+
+// fixed-capacity variant of CircularBuffer that stores its elements inline (e.g. on the stack) instead of on the heap
+// the capacity N is fixed at compile time
+// slots are stored as Option<T>, so unlike CircularBuffer there is no Default bound on T
+pub struct CircularBufferArray<T, const N: usize> {
+    // buffer that holds the actual data, a slot is None when it isn't in use
+    buffer: [Option<T>; N],
+    // index of where the data starts in the buffer (the "head")
+    index_start: usize,
+    // non-inclusive index of where the data stops in the buffer (the "tail")
+    index_next_free: usize,
+    // keep track of amount of elements currently stored, see CircularBuffer
+    size: usize
+}
+
+impl<T, const N: usize> CircularBufferArray<T, N> {
+
+    // this is a const fn, so it can be used to initialise a static buffer (e.g. inside a Mutex)
+    pub const fn new() -> Self {
+        Self {
+            buffer: [const { None }; N],
+            index_start: 0,
+            index_next_free: 0,
+            size: 0
+        }
+    }
+
+    pub fn write(&mut self, value: T) -> Result<(), &'static str> {
+        if !self.is_full() {
+            self.buffer[self.index_next_free] = Some(value);
+            self.index_next_free = Self::increase_index(self.index_next_free);
+            self.size += 1;
+            Ok(())
+        } else {
+            Err("CircularBuffer is full")
+        }
+    }
+
+    pub fn read(&mut self) -> Result<T, &'static str> {
+        if !self.is_empty() {
+            let result = self.buffer[self.index_start].take().unwrap();
+            self.index_start = Self::increase_index(self.index_start);
+            self.size -= 1;
+            Ok(result)
+        } else {
+            Err("CircularBuffer is empty")
+        }
+    }
+
+    pub fn peek(&self) -> Result<&T, &'static str> {
+        if !self.is_empty() {
+            Ok(self.buffer[self.index_start].as_ref().unwrap())
+        } else {
+            Err("CircularBuffer is empty")
+        }
+    }
+
+    // returns the amount of elements currently inside the buffer
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    // returns the *maximum* amount of elements that the buffer can hold, which is always N
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.size == N
+    }
+
+    pub fn clear(&mut self) {
+        // we read the rest of the buffer, to ensure the remaining elements are dropped from memory properly
+        while !self.is_empty() {
+            self.read().unwrap();
+        }
+    }
+
+    // private function that increases the index, overflowing if we're going beyond the capacity
+    fn increase_index(index: usize) -> usize {
+        if index == N - 1 {
+            0
+        } else {
+            index + 1
+        }
+    }
+
+}
+
+impl<T, const N: usize> Default for CircularBufferArray<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// moves the elements into a heap allocated buffer with the same capacity
+impl<T: Default + Clone, const N: usize> From<CircularBufferArray<T, N>> for CircularBuffer<T> {
+    fn from(mut array: CircularBufferArray<T, N>) -> Self {
+        let mut result = CircularBuffer::new(N);
+        while let Ok(value) = array.read() {
+            result.write(value).unwrap();
+        }
+        result
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{CircularBuffer, CircularBufferArray};
+
+    #[test]
+    fn test_basic_write_read() {
+        let mut buf = CircularBufferArray::<u8, 4>::new();
+        assert!(buf.is_empty());
+        assert_eq!(buf.capacity(), 4);
+        assert!(buf.peek().is_err());
+
+        buf.write(1).unwrap();
+        assert_eq!(buf.size(), 1);
+        assert_eq!(buf.index_next_free, 1);
+        assert_eq!(*buf.peek().unwrap(), 1);
+        assert_eq!(buf.read().unwrap(), 1);
+        assert!(buf.read().is_err());
+    }
+
+    #[test]
+    fn test_wrap_around() {
+        let mut buf = CircularBufferArray::<String, 3>::new();
+        for i in 0..10 {
+            buf.write(i.to_string()).unwrap();
+            if buf.is_full() {
+                assert!(buf.write(String::from("x")).is_err());
+                assert_eq!(buf.read().unwrap(), (i - 2).to_string());
+            }
+        }
+        assert_eq!(buf.size(), 2);
+        assert_eq!(buf.index_start, 2);
+        assert_eq!(buf.index_next_free, 1);
+
+        // read slots don't hold on to their old elements
+        assert!(buf.buffer[1].is_none());
+
+        buf.clear();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_static() {
+        use std::sync::Mutex;
+
+        static BUF: Mutex<CircularBufferArray<u8, 2>> = Mutex::new(CircularBufferArray::new());
+        const EMPTY: CircularBufferArray<u8, 2> = CircularBufferArray::new();
+        const _: () = assert!(EMPTY.is_empty() && !EMPTY.is_full() && EMPTY.capacity() == 2);
+
+        BUF.lock().unwrap().write(1).unwrap();
+        assert_eq!(BUF.lock().unwrap().read().unwrap(), 1);
+        assert_eq!(BUF.lock().unwrap().size(), 0);
+    }
+
+    #[test]
+    fn test_into_heap_buffer() {
+        let mut buf = CircularBufferArray::<u8, 3>::new();
+        buf.write(1).unwrap();
+        buf.write(2).unwrap();
+        let heap = CircularBuffer::from(buf);
+        assert_eq!(heap.capacity(), 3);
+        assert_eq!(heap.peek_many(2).unwrap(), vec![1,2]);
+    }
+
+}
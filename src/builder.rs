@@ -0,0 +1,117 @@
+use crate::CircularBuffer;
+
+// fluent builder that combines the different construction options of a CircularBuffer, see CircularBuffer::builder()
+// the capacity is required, everything else is optional:
+//
+//   let buf = CircularBuffer::<u8>::builder().capacity(64).overwrite(true).fill(0).build().unwrap();
+pub struct CircularBufferBuilder<T> {
+    capacity: Option<usize>,
+    overwrite: bool,
+    fill: Option<T>
+}
+
+impl<T: Default + Clone> CircularBufferBuilder<T> {
+
+    pub fn new() -> Self {
+        Self {
+            capacity: None,
+            overwrite: false,
+            fill: None
+        }
+    }
+
+    // the *maximum* amount of elements that the buffer can hold (required)
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    // if true, writing to a full buffer overwrites the oldest element instead of returning an error
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    // fills the buffer completely with clones of value, so it starts out full
+    pub fn fill(mut self, value: T) -> Self {
+        self.fill = Some(value);
+        self
+    }
+
+    // kept for callers that want to be explicit about it, but it does nothing: the internal buffer is always
+    // initialised with T::default() when it's created, so every slot has already been written before build() returns
+    pub fn prefault(self, _prefault: bool) -> Self {
+        self
+    }
+
+    pub fn build(self) -> Result<CircularBuffer<T>, &'static str> {
+        let capacity = self.capacity.ok_or("CircularBuffer capacity is required")?;
+
+        let mut buffer = CircularBuffer::new(capacity);
+        buffer.set_overwrite(self.overwrite);
+
+        if let Some(value) = self.fill {
+            buffer.fill(value);
+        }
+
+        Ok(buffer)
+    }
+
+}
+
+impl<T: Default + Clone> Default for CircularBufferBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{CircularBuffer, CircularBufferBuilder};
+
+    #[test]
+    fn test_build_defaults() {
+        let buf = CircularBuffer::<u8>::builder().capacity(4).build().unwrap();
+        assert_eq!(buf.capacity(), 4);
+        assert!(buf.is_empty());
+        assert!(!buf.is_overwriting());
+
+        assert!(CircularBufferBuilder::<u8>::new().overwrite(true).build().is_err());
+    }
+
+    #[test]
+    fn test_build_combinations() {
+        let mut buf = CircularBuffer::<u8>::builder().capacity(3).overwrite(true).fill(7).prefault(true).build().unwrap();
+        assert!(buf.is_overwriting());
+        assert!(buf.is_full());
+        assert_eq!(buf.peek_many(3).unwrap(), vec![7,7,7]);
+
+        // overwrite mode, so writing to a full buffer succeeds
+        buf.write(1).unwrap();
+        assert_eq!(buf.peek_many(3).unwrap(), vec![7,7,1]);
+
+        let mut buf = CircularBuffer::<u8>::builder().fill(5).capacity(2).build().unwrap();
+        assert!(!buf.is_overwriting());
+        assert!(buf.is_full());
+        assert!(buf.write(1).is_err());
+    }
+
+    #[test]
+    fn test_build_prefault() {
+        // prefaulting doesn't change the resulting buffer
+        let mut buf = CircularBuffer::<u8>::builder().capacity(4).prefault(true).build().unwrap();
+        assert_eq!(buf.capacity(), 4);
+        assert!(buf.is_empty());
+        assert!(!buf.is_overwriting());
+        buf.write_many(&[1,2,3,4]).unwrap();
+        assert!(buf.write(5).is_err());
+
+        let buf = CircularBuffer::<u8>::builder().capacity(2).fill(3).overwrite(true).prefault(true).build().unwrap();
+        assert!(buf.is_full());
+        assert!(buf.is_overwriting());
+        assert_eq!(buf.peek_many(2).unwrap(), vec![3,3]);
+    }
+
+}
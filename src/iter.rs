@@ -0,0 +1,277 @@
+use core::slice;
+use std::borrow::Cow;
+
+use crate::{CircularBuffer, RawBuffer, Storage};
+
+// iterator over references to the elements of a buffer, oldest first, see CircularBuffer::iter()
+// internally the elements are stored in at most two contiguous runs, because the data can wrap around
+pub struct Iter<'a, T> {
+    first: slice::Iter<'a, T>,
+    second: slice::Iter<'a, T>
+}
+
+impl<'a, T> Iter<'a, T> {
+    pub(crate) fn new(first: &'a [T], second: &'a [T]) -> Self {
+        Self {
+            first: first.iter(),
+            second: second.iter()
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.first.next().or_else(|| self.second.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.first.len() + self.second.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.second.next_back().or_else(|| self.first.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+// iterator over mutable references to the elements of a buffer, oldest first
+pub struct IterMut<'a, T> {
+    first: slice::IterMut<'a, T>,
+    second: slice::IterMut<'a, T>
+}
+
+impl<'a, T> IterMut<'a, T> {
+    pub(crate) fn new(first: &'a mut [T], second: &'a mut [T]) -> Self {
+        Self {
+            first: first.iter_mut(),
+            second: second.iter_mut()
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.first.next().or_else(|| self.second.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.first.len() + self.second.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.second.next_back().or_else(|| self.first.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+// iterator over all overlapping windows of a fixed size, oldest first, see CircularBuffer::windows()
+// a window is borrowed from the internal buffer when its elements are contiguous in memory, and only
+// cloned into a Vec when it straddles the end of the internal buffer
+pub struct Windows<'a, T, S = RawBuffer<T>> {
+    buffer: &'a CircularBuffer<T, S>,
+    window_size: usize,
+    // logical index of the first element of the next window
+    index: usize
+}
+
+impl<'a, T, S> Windows<'a, T, S> {
+    pub(crate) fn new(buffer: &'a CircularBuffer<T, S>, window_size: usize) -> Self {
+        Self {
+            buffer,
+            window_size,
+            index: 0
+        }
+    }
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> Iterator for Windows<'a, T, S> {
+    type Item = Cow<'a, [T]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index + self.window_size > self.buffer.size() {
+            return None;
+        }
+        let window = self.buffer.logical_range(self.index, self.window_size);
+        self.index += 1;
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.buffer.size() + 1).saturating_sub(self.index + self.window_size);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> ExactSizeIterator for Windows<'a, T, S> {}
+
+// iterator over non-overlapping chunks of a fixed size, oldest first, see CircularBuffer::chunks()
+// the last chunk is shorter if the amount of elements is not a multiple of the chunk size
+// like Windows, a chunk is borrowed from the internal buffer when its elements are contiguous in memory, and only
+// cloned into a Vec when it straddles the end of the internal buffer
+pub struct Chunks<'a, T, S = RawBuffer<T>> {
+    buffer: &'a CircularBuffer<T, S>,
+    chunk_size: usize,
+    // logical index of the first element of the next chunk
+    index: usize
+}
+
+impl<'a, T, S> Chunks<'a, T, S> {
+    pub(crate) fn new(buffer: &'a CircularBuffer<T, S>, chunk_size: usize) -> Self {
+        Self {
+            buffer,
+            chunk_size,
+            index: 0
+        }
+    }
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> Iterator for Chunks<'a, T, S> {
+    type Item = Cow<'a, [T]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.buffer.size() - self.index;
+        if remaining == 0 {
+            return None;
+        }
+        let len = remaining.min(self.chunk_size);
+        let chunk = self.buffer.logical_range(self.index, len);
+        self.index += len;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.buffer.size() - self.index;
+        let len = remaining.div_ceil(self.chunk_size);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> ExactSizeIterator for Chunks<'a, T, S> {}
+
+// draining iterator that reads exactly n elements out of the front of a buffer, see CircularBuffer::drain_n()
+// the elements are moved out one by one, but the buffer itself is only updated when the iterator is dropped,
+// so dropping it early still removes all n elements (the ones that weren't yielded are dropped)
+pub struct DrainN<'a, T: Default + Clone, S: Storage<T> = RawBuffer<T>> {
+    buffer: &'a mut CircularBuffer<T, S>,
+    // total amount of elements to remove from the buffer
+    amount: usize,
+    // logical index of the next element to yield from the front
+    front: usize,
+    // non-inclusive logical index of the next element to yield from the back
+    back: usize
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> DrainN<'a, T, S> {
+    pub(crate) fn new(buffer: &'a mut CircularBuffer<T, S>, amount: usize) -> Self {
+        Self {
+            buffer,
+            amount,
+            front: 0,
+            back: amount
+        }
+    }
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> Iterator for DrainN<'a, T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let element = self.buffer.take_logical(self.front);
+        self.front += 1;
+        Some(element)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> DoubleEndedIterator for DrainN<'a, T, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.buffer.take_logical(self.back))
+    }
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> ExactSizeIterator for DrainN<'a, T, S> {}
+
+impl<'a, T: Default + Clone, S: Storage<T>> Drop for DrainN<'a, T, S> {
+    fn drop(&mut self) {
+        // drop the elements that weren't yielded, then remove all of them from the buffer at once
+        for logical_index in self.front..self.back {
+            self.buffer.take_logical(logical_index);
+        }
+        self.buffer.skip_front(self.amount);
+    }
+}
+
+// consuming iterator that reads the elements out of a buffer, oldest first
+pub struct IntoIter<T, S = RawBuffer<T>> {
+    buffer: CircularBuffer<T, S>
+}
+
+impl<T: Default + Clone, S: Storage<T>> Iterator for IntoIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.read().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.buffer.size(), Some(self.buffer.size()))
+    }
+}
+
+impl<T: Default + Clone, S: Storage<T>> DoubleEndedIterator for IntoIter<T, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.buffer.pop_back().ok()
+    }
+}
+
+impl<T: Default + Clone, S: Storage<T>> ExactSizeIterator for IntoIter<T, S> {}
+
+impl<T: Default + Clone, S: Storage<T>> IntoIterator for CircularBuffer<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { buffer: self }
+    }
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> IntoIterator for &'a CircularBuffer<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T: Default + Clone, S: Storage<T>> IntoIterator for &'a mut CircularBuffer<T, S> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
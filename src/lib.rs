@@ -1,71 +1,535 @@
 
-use core::{mem};
+use core::{fmt, mem, ptr};
+use core::marker::PhantomData;
+use core::sync::atomic::{compiler_fence, Ordering as AtomicOrdering};
+use core::ops::{Add, Index, IndexMut, Range};
+use core::cmp::Ordering as CmpOrdering;
+use core::hash::{Hash, Hasher};
+use std::borrow::Cow;
+use std::io;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-pub struct CircularBuffer<T> {
+mod array;
+mod builder;
+mod iter;
+mod policy;
+mod raw;
+mod stats;
+mod storage;
+#[cfg(feature = "sync")]
+mod subscription;
+#[cfg(feature = "futures")]
+mod stream;
+#[cfg(feature = "std")]
+mod sync;
+
+pub use crate::array::CircularBufferArray;
+pub use crate::builder::CircularBufferBuilder;
+pub use crate::iter::{Chunks, DrainN, IntoIter, Iter, IterMut, Windows};
+pub use crate::policy::{DropNewest, ErrorPolicy, Overflow, OverflowPolicy, OverwriteOldest};
+use crate::policy::BoxedPolicy;
+pub use crate::raw::RawBuffer;
+pub use crate::stats::StatsCircularBuffer;
+pub use crate::storage::Storage;
+#[cfg(feature = "sync")]
+pub use crate::subscription::Subscription;
+#[cfg(feature = "futures")]
+pub use crate::stream::BufferStream;
+#[cfg(feature = "std")]
+pub use crate::sync::SyncCircularBuffer;
+#[cfg(feature = "sync")]
+use std::sync::Arc;
+#[cfg(feature = "sync")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// the elements are stored in S, a heap allocation by default, see Storage for other backends
+pub struct CircularBuffer<T, S = RawBuffer<T>> {
     // maximum amount of elements the buffer can hold
     capacity: usize,
     // buffer that holds the actual data
-    buffer: Box<[T]>,
+    buffer: S,
     // index of where the data starts in the buffer (the "head")
     index_start: usize,
     // non-inclusive index of where the data stops in the buffer (the "tail")
     index_next_free: usize,
     // keep track of amount of elements currently stored, it also makes things easier to understand in the internal code
     // also solves the problem that if index_start == index_next_free, we don't know if it's full or empty
+    size: usize,
+    // decides what write() and write_many() do when the buffer is full, ErrorPolicy by default
+    policy: Box<dyn BoxedPolicy>,
+    // if true, write() and write_many() double the capacity when the buffer is full, see new_growable()
+    growable: bool,
+    // upper limit for the capacity of a growable buffer, None means it can grow without limit
+    max_capacity: Option<usize>,
+    // optional callback that is invoked every time write() fails because the buffer is full
+    on_full: Option<Box<dyn FnMut() + Send + Sync>>,
+    // amount of elements saturating_write() (or the DropNewest policy) dropped because the buffer was full
+    dropped: u64,
+    // the highest size the buffer reached, see high_water_mark()
+    high_water_mark: usize,
+    // total amount of elements ever written to the tail, used by subscriptions to know what they haven't seen yet
+    #[cfg(feature = "sync")]
+    written: Arc<AtomicU64>,
+    // tells the compiler we own values of type T, even though they are stored in S
+    _marker: PhantomData<T>
+}
+
+// describes how a single logical position differs between two buffers, see CircularBuffer::diff()
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffKind<T> {
+    // both buffers have an element at this position, but they are not equal
+    Changed { from: T, to: T },
+    // only this buffer has an element at this position, the other buffer is shorter
+    OnlyInSelf(T),
+    // only the other buffer has an element at this position, this buffer is shorter
+    OnlyInOther(T)
+}
+
+// the position of the elements in a buffer at some point in time, see CircularBuffer::snapshot()
+// it only holds indices, the elements themselves are not copied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    capacity: usize,
+    index_start: usize,
+    index_next_free: usize,
     size: usize
 }
 
-impl<T: Default + Clone + ToString> CircularBuffer<T> {
+impl<T: Default + Clone> CircularBuffer<T> {
 
+    // a capacity of 0 is allowed, such a buffer is always empty and full at the same time, so every write fails
+    // use try_new() to reject a capacity of 0 instead
     pub fn new(capacity: usize) -> Self {
 
-        let result = Self {
-            capacity,
-            buffer: vec!(T::default(); capacity).into_boxed_slice(),
-            index_start: 0,
-            index_next_free: 0,
-            size: 0
+        Self::from_raw_parts(RawBuffer::new_with(capacity, 1, T::default), 0)
+    }
+
+    // alias for new(), following the naming convention of the standard collections
+    // both create an empty buffer that can hold *exactly* capacity elements, it never grows
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::new(capacity)
+    }
+
+    // creates a buffer that starts with the initial capacity and doubles it whenever an element is written while it's
+    // full, until the optional max capacity is reached, after which writing to a full buffer fails like usual
+    // this applies to every method that adds elements, including push_front(), write_slice() and append()
+    // panics if max is smaller than initial
+    pub fn new_growable(initial: usize, max: Option<usize>) -> Self {
+        assert!(max.is_none_or(|max| max >= initial), "CircularBuffer max capacity must not be smaller than the initial capacity");
+        let mut buf = Self::new(initial);
+        buf.growable = true;
+        buf.max_capacity = max;
+        buf
+    }
+
+    // creates an empty buffer in overwrite mode, where writing to a full buffer overwrites the oldest element instead
+    // of failing, see set_overwrite()
+    pub fn new_overwriting(capacity: usize) -> Self {
+        let mut buf = Self::new(capacity);
+        buf.set_overwrite(true);
+        buf
+    }
+
+    // same as new(), but returns an error instead of aborting the process when the capacity is too large to allocate
+    // use this when the capacity comes from untrusted input
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, &'static str> {
+        Ok(Self::from_raw_parts(RawBuffer::try_new_with(capacity, 1, T::default)?, 0))
+    }
+
+    // returns a builder that combines the different construction options, see CircularBufferBuilder
+    pub fn builder() -> CircularBufferBuilder<T> {
+        CircularBufferBuilder::new()
+    }
+
+    // same as new(), but returns an error instead of creating a buffer that can never hold any elements
+    pub fn try_new(capacity: usize) -> Result<Self, &'static str> {
+        if capacity == 0 {
+            return Err("CircularBuffer capacity must be larger than 0");
+        }
+        Ok(Self::new(capacity))
+    }
+
+    // creates a new buffer for DMA/SIMD style use, where the internal buffer starts at an address that is a multiple
+    // of alignment_bytes (which must be a power of two), and its total size in bytes is a multiple of alignment_bytes
+    // to achieve the latter, the capacity is rounded up, so it will be at least min_capacity
+    // when the capacity changes later on (e.g. resize_keep_newest() or grow_to()) the new internal buffer keeps the
+    // alignment of its start address, but it gets exactly the requested capacity, so the size guarantee only holds
+    // for the capacity the buffer was created with
+    pub fn new_aligned(min_capacity: usize, alignment_bytes: usize) -> Self {
+        assert!(alignment_bytes.is_power_of_two(), "CircularBuffer alignment must be a power of two");
+
+        // the smallest amount of elements that spans a whole multiple of the alignment
+        let element_size = mem::size_of::<T>();
+        let step = if element_size == 0 {
+            1
+        } else {
+            alignment_bytes / (1 << element_size.trailing_zeros().min(alignment_bytes.trailing_zeros()))
         };
+        let capacity = min_capacity.div_ceil(step) * step;
 
-        result
+        Self::from_raw_parts(RawBuffer::new_with(capacity, alignment_bytes, T::default), 0)
+    }
+
+    // creates a new buffer and fills it by expanding the (value, count) pairs, see run_length_encode()
+    pub fn from_run_length(capacity: usize, runs: &[(T, usize)]) -> Result<Self, &'static str> {
+        let total = runs.iter().try_fold(0usize, |total, (_, count)| total.checked_add(*count));
+        if total.is_none_or(|total| total > capacity) {
+            return Err("CircularBuffer does not have enough space for the provided elements");
+        }
+
+        let mut result = Self::new(capacity);
+        for (value, count) in runs {
+            for _ in 0..*count {
+                result.write(value.clone())?;
+            }
+        }
+        Ok(result)
+    }
+
+    // creates a new buffer with the given capacity that holds f(0), f(1), ..., f(count - 1), oldest first,
+    // the remaining capacity - count slots are free, fails (without calling f) if count is larger than the capacity
+    pub fn from_fn<F: FnMut(usize) -> T>(capacity: usize, count: usize, f: F) -> Result<Self, &'static str> {
+        if count > capacity {
+            return Err("CircularBuffer does not have enough space for the provided elements");
+        }
+        let mut result = Self::new(capacity);
+        result.write_iter((0..count).map(f));
+        Ok(result)
+    }
+
+    // creates a new buffer with the given capacity that starts with clones of the elements of data
+    // unlike From<&[T]>, the capacity can be larger than the slice, fails if the slice doesn't fit
+    pub fn try_from_slice(capacity: usize, data: &[T]) -> Result<Self, &'static str> {
+        let mut result = Self::new(capacity);
+        result.write_many(data)?;
+        Ok(result)
+    }
+
+    // creates a new buffer with the given capacity that starts with the elements of the deque, front first,
+    // the elements are moved, not cloned, fails if the deque has more elements than the capacity
+    pub fn from_vec_deque(capacity: usize, deque: VecDeque<T>) -> Result<Self, &'static str> {
+        if deque.len() > capacity {
+            return Err("CircularBuffer does not have enough space for the provided elements");
+        }
+        let mut result = Self::new(capacity);
+        result.write_iter(deque);
+        Ok(result)
+    }
+
+}
+
+impl<T: Default + Clone, S: Storage<T>> CircularBuffer<T, S> {
+
+    // creates an empty buffer that uses the given storage, the capacity is the length of the storage
+    // the elements that are already in the storage are treated as unused slots
+    pub fn from_storage(storage: S) -> Self {
+        Self::from_raw_parts(storage, 0)
     }
 
+    // writes an element, if the buffer is full the overflow policy decides what happens (see set_overflow_policy()),
+    // by default it fails
+    // in overwrite mode (see set_overwrite()) the oldest element is dropped instead, so it never fails,
+    // except for a buffer with a capacity of 0, which can't hold any element
     pub fn write(&mut self, value: T) -> Result<(), &'static str>{
-        if !self.is_full() {
-            self.buffer[self.index_next_free] = value;
-            self.index_next_free = self.increase_index(self.index_next_free);
-            self.size += 1;
-            Ok(())
+        self.grow_to_fit(1);
+        if self.is_full() {
+            match self.policy.on_overflow() {
+                Overflow::EvictOldest if !self.is_empty() => {
+                    self.read()?;
+                },
+                Overflow::DropNewest => {
+                    self.dropped += 1;
+                    return Ok(());
+                },
+                _ => {
+                    if let Some(callback) = self.on_full.as_mut() {
+                        callback();
+                    }
+                    return Err("CircularBuffer is full");
+                }
+            }
+        }
+        self.buffer[self.index_next_free] = value;
+        self.index_next_free = self.increase_index(self.index_next_free);
+        self.size += 1;
+        self.size_increased(1);
+        Ok(())
+    }
+
+    // replaces the overflow policy, which decides what write() and write_many() do when there is not enough space:
+    // fail (ErrorPolicy, the default), overwrite the oldest elements (OverwriteOldest) or drop the new elements
+    // (DropNewest), or anything a custom OverflowPolicy decides, the other write methods are not affected
+    pub fn set_overflow_policy<P: OverflowPolicy + Clone>(&mut self, policy: P) {
+        self.policy = Box::new(policy);
+    }
+
+    // switches overwrite mode on or off, this is a shortcut for set_overflow_policy(OverwriteOldest) or
+    // set_overflow_policy(ErrorPolicy)
+    pub fn set_overwrite(&mut self, overwrite: bool) {
+        if overwrite {
+            self.set_overflow_policy(OverwriteOldest);
         } else {
-            Err("CircularBuffer is full")
+            self.set_overflow_policy(ErrorPolicy);
+        }
+    }
+
+    // returns true if the overflow policy is the built-in OverwriteOldest (see set_overwrite()), a custom policy is
+    // never recognised, even if it always returns Overflow::EvictOldest
+    pub fn is_overwriting(&self) -> bool {
+        self.policy.as_any().is::<OverwriteOldest>()
+    }
+
+    // registers a callback that is invoked every time write() fails because the buffer is full
+    // this replaces any previously registered callback
+    // the callback must be Send + Sync, so the buffer itself can still be moved to and shared with other threads
+    pub fn on_full(&mut self, callback: Box<dyn FnMut() + Send + Sync>) {
+        self.on_full = Some(callback);
+    }
+
+    // writes an element, or drops it if the buffer is full, so unlike write() this never fails,
+    // and unlike write_overwrite() the elements that are already in the buffer are kept
+    // the dropped elements are counted, see dropped_count()
+    pub fn saturating_write(&mut self, value: T) {
+        self.grow_to_fit(1);
+        if self.is_full() {
+            self.dropped += 1;
+            return;
+        }
+        // can't fail, we checked above that the buffer is not full
+        self.write(value).unwrap();
+    }
+
+    // returns how many elements saturating_write() (or write() and write_many() with the DropNewest policy) dropped
+    // since the buffer was created, or since the last reset_dropped_count()
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped
+    }
+
+    pub fn reset_dropped_count(&mut self) {
+        self.dropped = 0;
+    }
+
+    // returns the highest amount of elements the buffer held at any point since it was created, or since the last
+    // reset_high_water_mark(), this shows how much of the capacity is actually needed
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark
+    }
+
+    // starts tracking the high water mark again from the current size
+    pub fn reset_high_water_mark(&mut self) {
+        self.high_water_mark = self.size;
+    }
+
+    // writes an element, overwriting (and returning) the oldest element if the buffer is full
+    pub fn write_overwrite(&mut self, value: T) -> Option<T> {
+        let mut evicted = None;
+        self.write_overwrite_with(value, |old_value| evicted = Some(old_value));
+        evicted
+    }
+
+    // same as write_overwrite(), but the overwritten element is passed to on_evict instead of being returned
+    // on_evict is only called when the buffer was full
+    pub fn write_overwrite_with<F: FnMut(T)>(&mut self, value: T, mut on_evict: F) {
+        if self.capacity == 0 {
+            // nothing can be stored, so the new element is evicted right away
+            on_evict(value);
+            return;
+        }
+        if self.is_full() {
+            let old_value = self.read().unwrap();
+            on_evict(old_value);
+        }
+        self.write(value).unwrap();
+    }
+
+    // writes all elements in order, overwriting (and dropping) the oldest elements as needed, so the buffer ends up
+    // with the newest elements, like calling write_overwrite() for each of them
+    // elements that would be overwritten by the same slice anyway are skipped instead of being cloned
+    pub fn extend_from_slice_overwrite(&mut self, values: &[T]) {
+        let skip = values.len().saturating_sub(self.capacity);
+        for element in &values[skip..] {
+            self.write_overwrite(element.clone());
+        }
+    }
+
+    // same as extend_from_slice_overwrite(), but for any iterator: every element is written, and once the buffer is
+    // full the oldest elements are overwritten (and dropped), so it ends up with the newest elements of the iterator
+    // the iterator is always consumed completely, see write_iter() for a version that stops when the buffer is full
+    pub fn extend_overwrite<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.write_overwrite(value);
         }
     }
 
+    // writes all elements in order, this is all-or-nothing: if they don't all fit, nothing is written and an error is
+    // returned (the only exception is a panic inside T::clone(), which leaves the elements written so far in place)
+    // it's panic-safe: every element is cloned before it's stored, so after such a panic the buffer is still valid,
+    // and holds exactly the elements that were written before the clone that panicked
+    // values can't borrow from this buffer (write_many() takes &mut self), so reading them while writing is always fine
+    // in overwrite mode (see set_overwrite()) the oldest elements are dropped to make room instead, so the buffer ends
+    // up with the newest elements, like extend_from_slice_overwrite(), the overflow policy is asked once for the whole
+    // slice, with DropNewest the elements that fit are written and the rest is dropped
+    // writing an empty slice always succeeds and does nothing, even if the buffer is full (the same goes for reading or
+    // peeking 0 elements with the other bulk methods)
     pub fn write_many(&mut self, values: &[T]) -> Result<(), &'static str>{
 
+        self.grow_to_fit(values.len());
         if values.len() > self.capacity - self.size() {
-            return Err("CircularBuffer does not have enough space for the provided elements");
+            match self.policy.on_overflow() {
+                Overflow::EvictOldest if self.capacity > 0 => {
+                    self.extend_from_slice_overwrite(values);
+                    return Ok(());
+                },
+                Overflow::DropNewest => {
+                    let written = self.write_partial(values);
+                    self.dropped += (values.len() - written) as u64;
+                    return Ok(());
+                },
+                _ => return Err("CircularBuffer does not have enough space for the provided elements")
+            }
         }
 
         for element in values {
-            self.write(element.clone())?;
+            // can't fail, we checked above that all elements fit, so the buffer can't become full halfway
+            self.write(element.clone()).unwrap();
+        }
+
+        Ok(())
+
+    }
+
+    // same as write_many(), but on failure the error is the amount of elements that didn't fit,
+    // so the caller knows how many to split off, e.g. write_many(&values[..values.len() - overflow])
+    pub fn checked_write_many(&mut self, values: &[T]) -> Result<(), usize> {
+        // write_many() only fails if there is not enough space, and in that case it didn't write anything
+        self.write_many(values).map_err(|_| values.len() - (self.capacity - self.size))
+    }
+
+    // same as write_many(), but for an iterator with a known length (e.g. a mapped range or array), so the elements don't
+    // have to be collected into a slice first, it's all-or-nothing: if they don't all fit, nothing is taken from the
+    // iterator and an error is returned, the overflow policy doesn't apply here
+    pub fn write_all_iter<I>(&mut self, iter: I) -> Result<(), &'static str> where I: IntoIterator<Item = T>, I::IntoIter: ExactSizeIterator {
+        let iter = iter.into_iter();
+        let len = iter.len();
+        self.grow_to_fit(len);
+        if len > self.capacity - self.size {
+            return Err("CircularBuffer does not have enough space for the provided elements");
+        }
+        // take() guards against an iterator that yields more elements than its len() promised
+        for value in iter.take(len) {
+            // can't fail, we checked above that len elements fit
+            self.write(value).unwrap();
+        }
+        Ok(())
+    }
+
+    // writes as many elements as fit, from the front of the slice, and returns how many were written
+    // unlike write_many(), running out of space is not an error, so the rest can be written later,
+    // e.g. with write_partial(&values[written..])
+    pub fn write_partial(&mut self, values: &[T]) -> usize {
+        self.grow_to_fit(values.len());
+        let amount = values.len().min(self.capacity - self.size);
+        for element in &values[..amount] {
+            // can't fail, amount is limited to the free space, so the buffer can't become full halfway
+            self.write(element.clone()).unwrap();
+        }
+        amount
+    }
+
+    // same as write_many(), but for Copy types the values are copied in (at most) two bulk copies instead of one by one
+    pub fn write_slice(&mut self, values: &[T]) -> Result<(), &'static str> where T: Copy {
+        self.grow_to_fit(values.len());
+        if values.len() > self.capacity - self.size() {
+            return Err("CircularBuffer does not have enough space for the provided elements");
         }
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        // the first run goes up to the end of the internal buffer, the rest wraps around to the start
+        let first_len = values.len().min(self.capacity - self.index_next_free);
+        let (first, second) = values.split_at(first_len);
+        self.buffer[self.index_next_free..self.index_next_free + first_len].copy_from_slice(first);
+        self.buffer[..second.len()].copy_from_slice(second);
 
+        self.index_next_free = (self.index_next_free + values.len()) % self.capacity;
+        self.size += values.len();
+        self.size_increased(values.len());
         Ok(())
+    }
+
+    // writes elements from the iterator until it ends or the buffer is full, and returns how many were written
+    // unlike write_many(), running out of space is not an error, and an element is never taken from the iterator
+    // unless there is room for it, so the remaining elements can still be pulled from it afterwards
+    pub fn write_iter<I: IntoIterator<Item = T>>(&mut self, iter: I) -> usize {
+        let mut iter = iter.into_iter();
+        let mut written = 0;
+        loop {
+            self.grow_to_fit(1);
+            if self.is_full() {
+                break;
+            }
+            match iter.next() {
+                // can't fail, we checked above that the buffer is not full
+                Some(value) => self.write(value).unwrap(),
+                None => break
+            }
+            written += 1;
+        }
+        written
+    }
 
+    // writes all values (or none if there isn't enough space) and returns the logical index range they occupy,
+    // where index 0 is the oldest element in the buffer. The range is only valid until the next read.
+    // the overflow policy doesn't apply here: nothing is ever evicted or dropped, so a full buffer always fails
+    pub fn write_many_tracked(&mut self, values: &[T]) -> Result<Range<usize>, &'static str> {
+        self.grow_to_fit(values.len());
+        if values.len() > self.capacity - self.size {
+            return Err("CircularBuffer does not have enough space for the provided elements");
+        }
+        let start = self.size;
+        self.write_many(values)?;
+        Ok(start..start + values.len())
     }
 
+    // reads the oldest element, the slot it leaves behind is reset to T::default()
     pub fn read(&mut self) -> Result<T, &'static str> {
         if !self.is_empty() {
-            let result = mem::replace(&mut self.buffer[self.index_start], T::default());
+            let result = mem::take(&mut self.buffer[self.index_start]);
+            self.index_start = self.increase_index(self.index_start);
+            self.size -= 1;
+            Ok(result)
+        } else {
+            Err("CircularBuffer is empty")
+        }
+    }
+
+    // same as read(), but f is called with the element before it's returned, e.g. for logging or metrics
+    // f is not called if the buffer is empty
+    pub fn read_tap<F: FnMut(&T)>(&mut self, mut f: F) -> Result<T, &'static str> {
+        let value = self.read()?;
+        f(&value);
+        Ok(value)
+    }
+
+    // same as read(), but the slot is filled with the given placeholder instead of T::default()
+    // every slot of the internal buffer always holds an initialised value, so something has to take the place of the
+    // element that is read, this lets you pick a cheap placeholder (or reuse one) when T::default() is expensive,
+    // the placeholder is dropped when the slot is overwritten or when the buffer is dropped
+    // note that new(), clear() and friends still use T::default()
+    pub fn read_replace(&mut self, placeholder: T) -> Result<T, &'static str> {
+        if !self.is_empty() {
+            let result = mem::replace(&mut self.buffer[self.index_start], placeholder);
             self.index_start = self.increase_index(self.index_start);
-            self.size = self.size - 1;
+            self.size -= 1;
             Ok(result)
         } else {
             Err("CircularBuffer is empty")
         }
     }
 
+    // reads the oldest amount elements, fails (without reading anything) if the buffer holds less than amount elements
+    // reading 0 elements always succeeds and returns an empty Vec, even if the buffer is empty
     pub fn read_many(&mut self, amount: usize) -> Result<Vec<T>, &'static str> {
 
         if amount > self.size() {
@@ -80,6 +544,37 @@ impl<T: Default + Clone + ToString> CircularBuffer<T> {
         Ok(vec)
     }
 
+    // same as read_many(), but returns an iterator that moves the elements out one by one instead of collecting
+    // them into a Vec, see DrainN
+    // all n elements are removed from the buffer when the iterator is dropped, even if they weren't all consumed
+    pub fn drain_n(&mut self, n: usize) -> Result<DrainN<'_, T, S>, &'static str> {
+        if n > self.size() {
+            return Err("CircularBuffer does not contain the amount of requested elements");
+        }
+        Ok(DrainN::new(self, n))
+    }
+
+    // same as read_many(), but for Copy types the elements are copied out in (at most) two bulk copies
+    // the slots that were read are left as they are, instead of being reset to T::default()
+    pub fn read_slice(&mut self, amount: usize) -> Result<Vec<T>, &'static str> where T: Copy {
+        if amount > self.size() {
+            return Err("CircularBuffer does not contain the amount of requested elements");
+        }
+        if amount == 0 {
+            return Ok(Vec::new());
+        }
+
+        let (first, second) = self.as_slices();
+        let first_len = amount.min(first.len());
+        let mut vec: Vec<T> = Vec::with_capacity(amount);
+        vec.extend_from_slice(&first[..first_len]);
+        vec.extend_from_slice(&second[..amount - first_len]);
+
+        self.index_start = (self.index_start + amount) % self.capacity;
+        self.size -= amount;
+        Ok(vec)
+    }
+
     pub fn peek(&self) -> Result<&T, &'static str> {
         if !self.is_empty() {
             Ok(&self.buffer[self.index_start])
@@ -88,6 +583,86 @@ impl<T: Default + Clone + ToString> CircularBuffer<T> {
         }
     }
 
+    // returns a reference to the most recently written element (the "tail"), without removing it
+    pub fn peek_back(&self) -> Result<&T, &'static str> {
+        if !self.is_empty() {
+            Ok(&self.buffer[self.decrease_index(self.index_next_free)])
+        } else {
+            Err("CircularBuffer is empty")
+        }
+    }
+
+    // returns a clone of the oldest element (the "head"), or None if the buffer is empty
+    pub fn first(&self) -> Option<T> {
+        self.peek().ok().cloned()
+    }
+
+    // returns a clone of the most recently written element (the "tail"), or None if the buffer is empty
+    pub fn last(&self) -> Option<T> {
+        self.peek_back().ok().cloned()
+    }
+
+    // returns a reference to the n-th most recently written element, where 0 is the newest element (like peek_back())
+    // returns None if n is not smaller than the size
+    pub fn back_nth(&self, n: usize) -> Option<&T> {
+        if n < self.size {
+            Some(&self.buffer[(self.index_next_free + self.capacity - 1 - n) % self.capacity])
+        } else {
+            None
+        }
+    }
+
+    // returns a mutable reference to the oldest element (the "head"), so it can be modified in place
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        if !self.is_empty() {
+            Some(&mut self.buffer[self.index_start])
+        } else {
+            None
+        }
+    }
+
+    // alias for peek_mut()
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.peek_mut()
+    }
+
+    // replaces the oldest element with `new`, but only if it is equal to `expected`
+    // returns Ok(true) if the element was replaced, Ok(false) if it didn't match
+    pub fn compare_and_replace_front(&mut self, expected: &T, new: T) -> Result<bool, &'static str> where T: PartialEq {
+        if self.is_empty() {
+            return Err("CircularBuffer is empty");
+        }
+        if self.buffer[self.index_start] == *expected {
+            self.buffer[self.index_start] = new;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    // drops the oldest n elements without returning them, so unlike read_many() nothing is allocated
+    // for types without drop glue (e.g. Copy types) only the start index moves
+    // fails (without dropping anything) if the buffer holds less than n elements
+    pub fn shift_out(&mut self, n: usize) -> Result<(), &'static str> {
+        if n > self.size {
+            return Err("CircularBuffer does not contain the amount of requested elements");
+        }
+        if mem::needs_drop::<T>() {
+            for i in 0..n {
+                drop(self.take_logical(i));
+            }
+        }
+        self.skip_front(n);
+        Ok(())
+    }
+
+    // same as read_many(), but it returns None (and reads nothing) if the buffer holds less than n elements,
+    // e.g. for polling whether a complete frame of n elements has arrived
+    pub fn try_read_exact(&mut self, n: usize) -> Option<Vec<T>> {
+        self.read_many(n).ok()
+    }
+
+    // same as read_many(), but the elements are cloned instead of removed
     pub fn peek_many(&self, amount: usize) -> Result<Vec<T>, &'static str> {
 
         if amount > self.size() {
@@ -104,227 +679,4168 @@ impl<T: Default + Clone + ToString> CircularBuffer<T> {
         Ok(vec)
     }
 
+    // returns the oldest n elements as a slice, without copying them, if they are stored contiguously
+    // returns None if they wrap around the end of the internal buffer (use peek_many() instead),
+    // or if the buffer holds less than n elements
+    pub fn peek_chunk(&self, n: usize) -> Option<&[T]> {
+        if n > self.size {
+            return None;
+        }
+        let (first, _) = self.as_slices();
+        first.get(..n)
+    }
+
+    // same as peek_many(), but the elements are borrowed instead of cloned when they are stored contiguously,
+    // they are only cloned into a Vec if they wrap around the end of the internal buffer, see peek_chunk()
+    pub fn peek_cow(&self, n: usize) -> Result<Cow<'_, [T]>, &'static str> {
+        if n > self.size {
+            return Err("CircularBuffer does not contain the amount of requested elements");
+        }
+        Ok(self.logical_range(0, n))
+    }
+
+    // returns clones of the elements at the logical indices of range (0 is the oldest element), in order
+    // unlike peek_many(), this doesn't have to start at the oldest element
+    // fails if the range is inverted or ends beyond the size
+    pub fn peek_range(&self, range: Range<usize>) -> Result<Vec<T>, &'static str> {
+        if range.start > range.end || range.end > self.size {
+            return Err("CircularBuffer range out of bounds");
+        }
+        Ok(self.logical_range(range.start, range.end - range.start).into_owned())
+    }
+
+    // returns clones of the elements from logical index start up to the newest element, in order,
+    // or an empty Vec if start is not smaller than the size, it's the same as peek_range(start..size())
+    pub fn to_vec_from(&self, start: usize) -> Vec<T> {
+        if start >= self.size {
+            return Vec::new();
+        }
+        self.logical_range(start, self.size - start).into_owned()
+    }
+
+    // returns true if the value is currently somewhere inside the buffer
+    pub fn contains(&self, value: &T) -> bool where T: PartialEq {
+        let mut index = self.index_start;
+        for _ in 0..self.size {
+            if self.buffer[index] == *value {
+                return true;
+            }
+            index = self.increase_index(index);
+        }
+        false
+    }
+
+    // clamps every element currently inside the buffer into [min, max]
+    pub fn clamp_all(&mut self, min: T, max: T) where T: PartialOrd {
+        let mut index = self.index_start;
+        for _ in 0..self.size {
+            if self.buffer[index] < min {
+                self.buffer[index] = min.clone();
+            } else if self.buffer[index] > max {
+                self.buffer[index] = max.clone();
+            }
+            index = self.increase_index(index);
+        }
+    }
+
     // returns the amount of elements currently inside the buffer
     // not to be confused with capacity, which is the *maximum* amount of elements that the buffer can hold
-    pub fn size(&self) -> usize {
-        return self.size;
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    // alias for size(), following the naming convention of the standard collections, both are equivalent
+    pub const fn len(&self) -> usize {
+        self.size
     }
 
     // returns the *maximum* amount of elements that the buffer can hold
-    pub fn capacity(&self) -> usize {
+    pub const fn capacity(&self) -> usize {
         self.capacity
     }
 
     // if the start index equals the next_free index, then the buffer is empty
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.size == 0
     }
 
-    pub fn is_full(&self) -> bool {
+    pub const fn is_full(&self) -> bool {
         self.size == self.capacity
     }
 
+    // returns how full the buffer is, from 0.0 (empty) to 1.0 (full), e.g. for exporting metrics
+    // a buffer with a capacity of 0 is considered empty, so this never returns NaN
+    pub fn occupancy(&self) -> f64 {
+        if self.capacity == 0 {
+            return 0.0;
+        }
+        self.size as f64 / self.capacity as f64
+    }
+
 
+    // empties the buffer, this is the same as reset(): constant time unless the elements have a destructor
     pub fn clear(&mut self) {
-        // we read the rest of the buffer, to ensure the remaining elements are dropped from memory properly
-        while !self.is_empty() {
-            self.read().unwrap();
+        self.reset();
+    }
+
+    // empties the buffer by resetting the indices, without allocating
+    // every slot always holds a value, so an element can only be dropped by putting T::default() in its place,
+    // that's only done for types that actually need to be dropped (e.g. Vec or Box), for all other types this is
+    // constant time and no default values are constructed
+    pub fn reset(&mut self) {
+        if mem::needs_drop::<T>() {
+            for slot in self.iter_mut() {
+                drop(mem::take(slot));
+            }
         }
+        self.index_start = 0;
+        self.index_next_free = 0;
+        self.size = 0;
     }
 
-    pub fn print(&self) {
-        println!("{}", self.to_string());
+    // kept for backward compatibility, clear() is already constant time for Copy types
+    pub fn clear_fast(&mut self) where T: Copy {
+        self.reset();
     }
 
-    // private function that increases the index, overflowing if we're going beyond the capacity
-    // this function does not check if the increase makes sense, it's the responsibility of the caller!
-    fn increase_index(&self, index: usize) -> usize {
-        if index == self.capacity - 1 {
-            0
-        } else {
-            index + 1
+    // like clear_fast(), but first overwrites every slot of the internal buffer (not only the elements that are
+    // currently stored) with the default value, using volatile writes that the compiler can't optimize away
+    // this is meant for short-lived secrets (e.g. keys in a CircularBuffer<u8>) that shouldn't linger in memory,
+    // including in slots whose elements were already read
+    // it does NOT scrub copies that were made elsewhere: elements returned by read()/peek_many() and friends, copies
+    // on the stack, or old allocations that were left behind when the buffer was resized
+    pub fn secure_clear(&mut self) where T: Copy {
+        for slot in self.buffer.iter_mut() {
+            // safe because the slot is a valid and aligned reference, and Copy types have no destructor to skip
+            unsafe { ptr::write_volatile(slot, T::default()) };
         }
+        // prevent the compiler from moving later accesses to the buffer before the volatile writes
+        compiler_fence(AtomicOrdering::SeqCst);
+        self.clear_fast();
     }
 
-}
-
-impl<T: Default + Clone + ToString> ToString for CircularBuffer<T> {
-    fn to_string(&self) -> String {
-        let mut output: String = String::from("[");
+    // returns the trapezoidal integral of the elements (oldest first), treating them as samples spaced dt apart
+    // returns None if there are less than 2 elements
+    pub fn trapezoidal_integral(&self, dt: f64) -> Option<f64> where T: Into<f64> + Copy {
+        if self.size < 2 {
+            return None;
+        }
+        let mut area = 0.0;
+        let mut previous: f64 = (*self.peek().ok()?).into();
+        for value in self.iter().skip(1) {
+            let current: f64 = (*value).into();
+            area += (previous + current) * 0.5 * dt;
+            previous = current;
+        }
+        Some(area)
+    }
 
-        //is_wrapping: bool = self.index_next_free < self.index_start;
-        for i in 0 .. self.capacity {
-            if i >= self.index_start && i < self.index_next_free {
-                output += self.buffer[i].to_string().as_str();
-            } else {
-                output += "_";
+    // returns the distinct elements in the buffer, in the order they were first encountered (oldest first)
+    pub fn unique(&self) -> Vec<T> where T: Eq + Hash {
+        let mut seen: HashSet<&T> = HashSet::with_capacity(self.size);
+        let mut vec: Vec<T> = Vec::new();
+        let mut index = self.index_start;
+        for _ in 0..self.size {
+            if seen.insert(&self.buffer[index]) {
+                vec.push(self.buffer[index].clone());
             }
-            if i != self.capacity-1 {
-                output += ",";
+            index = self.increase_index(index);
+        }
+        vec
+    }
+
+    // counts how many times every distinct value occurs in the buffer, e.g. for a histogram over a rolling window
+    pub fn frequency(&self) -> HashMap<T, usize> where T: Eq + Hash {
+        let mut counts: HashMap<T, usize> = HashMap::new();
+        for value in self.iter() {
+            match counts.get_mut(value) {
+                Some(count) => *count += 1,
+                None => {
+                    counts.insert(value.clone(), 1);
+                }
             }
         }
-        output += "]";
-        output
+        counts
     }
-}
 
+    // inserts an element at the front of the buffer (the "head"), so it will be the first element to be read
+    pub fn push_front(&mut self, value: T) -> Result<(), &'static str> {
+        self.grow_to_fit(1);
+        if !self.is_full() {
+            self.index_start = self.decrease_index(self.index_start);
+            self.buffer[self.index_start] = value;
+            self.size += 1;
+            self.size_increased(0);
+            Ok(())
+        } else {
+            Err("CircularBuffer is full")
+        }
+    }
 
-#[cfg(test)]
-mod tests {
+    // removes and returns the most recently written element (the "tail"), so the buffer can be used as a stack
+    pub fn pop_back(&mut self) -> Result<T, &'static str> {
+        if !self.is_empty() {
+            self.index_next_free = self.decrease_index(self.index_next_free);
+            let result = mem::take(&mut self.buffer[self.index_next_free]);
+            self.size -= 1;
+            Ok(result)
+        } else {
+            Err("CircularBuffer is empty")
+        }
+    }
 
-    use crate::CircularBuffer;
-    use rand::distributions::{Distribution, Uniform};
+    // same as peek(), but without checking if the buffer is empty, only debug builds still check it
+    // Safety: the caller must ensure !is_empty(), calling this on an empty buffer is undefined behavior
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe fn peek_unchecked(&self) -> &T {
+        debug_assert!(!self.is_empty(), "CircularBuffer is empty");
+        self.buffer.get_unchecked(self.index_start)
+    }
+
+    // same as read(), but without checking if the buffer is empty, only debug builds still check it
+    // only that branch is skipped, the element is still moved out and the size is still updated like in read()
+    // Safety: the caller must ensure !is_empty(), calling this on an empty buffer is undefined behavior
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe fn read_unchecked(&mut self) -> T {
+        debug_assert!(!self.is_empty(), "CircularBuffer is empty");
+        let result = mem::take(self.buffer.get_unchecked_mut(self.index_start));
+        self.index_start = self.increase_index(self.index_start);
+        self.size -= 1;
+        result
+    }
+
+    // same as write(), but without checking if the buffer is full, only debug builds still check it
+    // the overflow policy and growing (see new_growable()) don't apply here
+    // Safety: the caller must ensure !is_full(), calling this on a full buffer is undefined behavior
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe fn write_unchecked(&mut self, value: T) {
+        debug_assert!(!self.is_full(), "CircularBuffer is full");
+        *self.buffer.get_unchecked_mut(self.index_next_free) = value;
+        self.index_next_free = self.increase_index(self.index_next_free);
+        self.size += 1;
+        self.size_increased(1);
+    }
+
+    // moves elements from the front of the fuller buffer to the back of the other one, until their sizes are
+    // as equal as possible (or the receiving buffer is full), returns the amount of elements that were moved
+    pub fn balance(a: &mut Self, b: &mut Self) -> usize {
+        let (from, to) = if a.size > b.size { (a, b) } else { (b, a) };
+
+        let mut moved = 0;
+        while from.size > to.size + 1 && !to.is_full() {
+            let value = from.read().unwrap();
+            to.write(value).unwrap();
+            moved += 1;
+        }
+        moved
+    }
+
+    // compares both buffers element by element (oldest first) and returns the logical positions where they differ
+    pub fn diff(&self, other: &Self) -> Vec<(usize, DiffKind<T>)> where T: PartialEq {
+        let mut result = Vec::new();
+        for i in 0..self.size.max(other.size) {
+            let ours = if i < self.size { Some(&self.buffer[self.physical_index(i)]) } else { None };
+            let theirs = if i < other.size { Some(&other.buffer[other.physical_index(i)]) } else { None };
+            match (ours, theirs) {
+                (Some(from), Some(to)) if from != to => {
+                    result.push((i, DiffKind::Changed { from: from.clone(), to: to.clone() }));
+                }
+                (Some(value), None) => result.push((i, DiffKind::OnlyInSelf(value.clone()))),
+                (None, Some(value)) => result.push((i, DiffKind::OnlyInOther(value.clone()))),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    // returns an iterator over references to the elements, oldest first
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (first, second) = self.as_slices();
+        Iter::new(first, second)
+    }
+
+    // calls f for every window of `window` consecutive elements (oldest first) and writes the results into out
+    // this produces size() - window + 1 results, out must have enough space for all of them or nothing is written
+    pub fn sliding_apply<U, F>(&self, window: usize, mut f: F, out: &mut CircularBuffer<U>) -> Result<(), &'static str>
+        where U: Default + Clone, F: FnMut(&[T]) -> U {
+
+        if window == 0 {
+            return Err("CircularBuffer window size must be larger than 0");
+        }
+
+        let amount = (self.size + 1).saturating_sub(window);
+        if amount > out.capacity() - out.size() {
+            return Err("CircularBuffer does not have enough space for the provided elements");
+        }
+
+        // only the windows that straddle the end of the internal buffer are cloned, see windows()
+        for values_window in self.windows(window) {
+            out.write(f(&values_window))?;
+        }
+
+        Ok(())
+    }
+
+    // returns an iterator over mutable references to the elements, oldest first
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (first, second) = self.as_mut_slices();
+        IterMut::new(first, second)
+    }
+
+    // keeps only the elements for which f returns true, preserving their order
+    // the remaining elements are moved towards the head, the removed ones are dropped
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let mut kept = 0;
+        for i in 0..self.size {
+            let index = self.physical_index(i);
+            if f(&self.buffer[index]) {
+                if kept != i {
+                    let kept_index = self.physical_index(kept);
+                    self.buffer.swap(kept_index, index);
+                }
+                kept += 1;
+            }
+        }
+
+        // everything after the kept elements was rejected
+        self.drop_back_from(kept);
+    }
+
+    // removes consecutive equal elements, so only the first element of every run remains, like Vec::dedup()
+    // the remaining elements are moved towards the head, the removed ones are dropped
+    pub fn dedup(&mut self) where T: PartialEq {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    // same as dedup(), but elements are considered equal if they have the same key
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&T) -> K>(&mut self, mut key: F) {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    // same as dedup(), but same_bucket(element, previous) decides whether an element is removed,
+    // where previous is the last element that was kept
+    pub fn dedup_by<F: FnMut(&T, &T) -> bool>(&mut self, mut same_bucket: F) {
+        if self.size == 0 {
+            return;
+        }
+        let mut kept = 1;
+        for i in 1..self.size {
+            let index = self.physical_index(i);
+            let previous_index = self.physical_index(kept - 1);
+            if !same_bucket(&self.buffer[index], &self.buffer[previous_index]) {
+                if kept != i {
+                    let kept_index = self.physical_index(kept);
+                    self.buffer.swap(kept_index, index);
+                }
+                kept += 1;
+            }
+        }
+
+        // everything after the kept elements was a duplicate
+        self.drop_back_from(kept);
+    }
+
+    // returns (value, count) pairs for every run of consecutive equal elements, oldest first
+    pub fn run_length_encode(&self) -> Vec<(T, usize)> where T: PartialEq {
+        let mut runs: Vec<(T, usize)> = Vec::new();
+        for value in self.iter() {
+            match runs.last_mut() {
+                Some((run_value, count)) if run_value == value => *count += 1,
+                _ => runs.push((value.clone(), 1))
+            }
+        }
+        runs
+    }
+
+    // returns the sum of all elements, or T::default() (zero for numeric types) if the buffer is empty
+    pub fn sum(&self) -> T where T: Add<Output = T> + Copy {
+        self.iter().fold(T::default(), |sum, value| sum + *value)
+    }
+
+    // returns the smallest element, or None if the buffer is empty
+    pub fn min_element(&self) -> Option<T> where T: Ord + Copy {
+        self.iter().min().copied()
+    }
+
+    // returns the largest element, or None if the buffer is empty
+    pub fn max_element(&self) -> Option<T> where T: Ord + Copy {
+        self.iter().max().copied()
+    }
+
+    // returns the average of all elements, or None if the buffer is empty
+    // this needs a lossless conversion to f64, see mean_by() for types like i64, u64 and usize
+    pub fn mean(&self) -> Option<f64> where T: Into<f64> + Copy {
+        self.mean_by(|value| (*value).into())
+    }
+
+    // returns the average of f applied to every element, or None if the buffer is empty
+    // e.g. mean_by(|value| *value as f64) for integer types that don't convert to f64 without loss
+    pub fn mean_by<F: FnMut(&T) -> f64>(&self, f: F) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        let sum: f64 = self.iter().map(f).sum();
+        Some(sum / self.size as f64)
+    }
+
+    // rotates the elements n positions to the left, so the element at logical index n becomes the oldest one
+    // the rotation only applies to the elements inside the buffer (modulo size), not to the free slots
+    // for a full buffer this only adjusts the indices, otherwise min(n, size - n) elements are moved
+    pub fn rotate_left(&mut self, n: usize) {
+        if self.size == 0 {
+            return;
+        }
+        let n = n % self.size;
+        if self.is_full() {
+            self.index_start = (self.index_start + n) % self.capacity;
+            self.index_next_free = self.index_start;
+        } else if n <= self.size - n {
+            // move elements from the head to the tail, one by one
+            // the runs can overlap when the buffer is almost full, which is fine because every element is moved
+            // (and its slot emptied) before the next one, a bulk version of this must use ptr::copy() for that reason,
+            // not ptr::copy_nonoverlapping()
+            for _ in 0..n {
+                let value = mem::take(&mut self.buffer[self.index_start]);
+                self.buffer[self.index_next_free] = value;
+                self.index_start = self.increase_index(self.index_start);
+                self.index_next_free = self.increase_index(self.index_next_free);
+            }
+        } else {
+            self.rotate_right(self.size - n);
+        }
+    }
+
+    // rotates the elements n positions to the right, so the newest n elements become the oldest ones
+    // see rotate_left()
+    pub fn rotate_right(&mut self, n: usize) {
+        if self.size == 0 {
+            return;
+        }
+        let n = n % self.size;
+        if self.is_full() {
+            self.rotate_left(self.size - n);
+        } else if n <= self.size - n {
+            // move elements from the tail to the head, one by one
+            for _ in 0..n {
+                self.index_start = self.decrease_index(self.index_start);
+                self.index_next_free = self.decrease_index(self.index_next_free);
+                let value = mem::take(&mut self.buffer[self.index_next_free]);
+                self.buffer[self.index_start] = value;
+            }
+        } else {
+            self.rotate_left(self.size - n);
+        }
+    }
+
+    // changes the capacity of the buffer, which requires a new internal buffer
+    // if the new capacity is smaller than the amount of elements, the oldest elements are dropped,
+    // so the newest new_capacity elements remain (in the same order)
+    // panics if the storage can't hold the new capacity, which never happens for the default heap storage
+    pub fn resize_keep_newest(&mut self, new_capacity: usize) {
+        self.truncate(new_capacity);
+        let reallocated = self.reallocate(new_capacity);
+        assert!(reallocated, "CircularBuffer storage can't hold {} elements", new_capacity);
+    }
+
+    // changes the capacity like resize_keep_newest(), and then fills the buffer up with values returned by f(),
+    // which are written after the existing elements, so the buffer is always full afterwards, like Vec::resize_with()
+    // panics if the storage can't hold the new capacity, which never happens for the default heap storage
+    pub fn resize_with<F: FnMut() -> T>(&mut self, new_capacity: usize, mut f: F) {
+        self.resize_keep_newest(new_capacity);
+        while !self.is_full() {
+            // can't fail, we checked that the buffer is not full
+            self.write(f()).unwrap();
+        }
+    }
+
+    // increases the capacity to min_capacity, the elements are kept (in the same order), which requires a new
+    // internal buffer, does nothing if the capacity is already at least min_capacity, so it never drops elements
+    // panics if the storage can't hold the new capacity, which never happens for the default heap storage
+    pub fn grow_to(&mut self, min_capacity: usize) {
+        if min_capacity > self.capacity {
+            let reallocated = self.reallocate(min_capacity);
+            assert!(reallocated, "CircularBuffer storage can't hold {} elements", min_capacity);
+        }
+    }
+
+    // reduces the capacity to the amount of elements, which requires a new internal buffer
+    // the buffer is full afterwards, so the next write() fails (or overwrites/grows, depending on the mode)
+    pub fn shrink_to_fit(&mut self) {
+        if self.capacity != self.size {
+            self.reallocate(self.size);
+        }
+    }
+
+    // drops the oldest elements until at most len elements remain, so the newest len elements are kept
+    // note that this is the opposite of Vec::truncate(), which keeps the first elements
+    pub fn truncate(&mut self, len: usize) {
+        while self.size > len {
+            self.read().unwrap();
+        }
+    }
+
+    // drops the oldest elements so that (at most) the newest n elements remain, same as truncate()
+    // does nothing if the buffer holds n elements or less
+    pub fn keep_newest(&mut self, n: usize) {
+        self.truncate(n);
+    }
+
+    // drops the newest elements so that (at most) the oldest n elements remain, like Vec::truncate()
+    // does nothing if the buffer holds n elements or less
+    pub fn keep_oldest(&mut self, n: usize) {
+        if n < self.size {
+            self.drop_back_from(n);
+        }
+    }
+
+    // writes clones of value into every slot, so the buffer becomes full, any existing elements are dropped
+    pub fn fill(&mut self, value: T) {
+        for slot in self.buffer.iter_mut() {
+            *slot = value.clone();
+        }
+        self.index_start = 0;
+        self.index_next_free = 0;
+        self.size = self.capacity;
+        self.size_increased(self.capacity);
+    }
+
+    // moves all elements of other to the back of this buffer (oldest first), leaving other empty
+    // if there isn't enough space for all of them, nothing is moved
+    pub fn append(&mut self, other: &mut Self) -> Result<(), &'static str> {
+        self.grow_to_fit(other.size());
+        if other.size() > self.capacity - self.size() {
+            return Err("CircularBuffer does not have enough space for the provided elements");
+        }
+        while let Ok(value) = other.read() {
+            self.write(value)?;
+        }
+        Ok(())
+    }
+
+    // returns the logical index (0 is the oldest element) of the first element for which f returns true
+    pub fn position<F: FnMut(&T) -> bool>(&self, f: F) -> Option<usize> {
+        self.iter().position(f)
+    }
+
+    // same as position(), but it searches from the newest element backwards, so it finds the newest match
+    // the returned index still counts from the oldest element, so it can be passed to get()
+    pub fn rposition<F: FnMut(&T) -> bool>(&self, f: F) -> Option<usize> {
+        self.iter().rposition(f)
+    }
+
+    // returns a reference to the first element (oldest first) for which f returns true
+    pub fn find<F: FnMut(&T) -> bool>(&self, mut f: F) -> Option<&T> {
+        self.iter().find(|value| f(value))
+    }
+
+    // returns an iterator over all overlapping windows of n consecutive elements, oldest first, see Windows
+    // yields nothing if n is larger than the amount of elements, n must be larger than 0
+    pub fn windows(&self, n: usize) -> Windows<'_, T, S> {
+        assert!(n != 0, "CircularBuffer window size must be larger than 0");
+        Windows::new(self, n)
+    }
+
+    // returns an iterator over non-overlapping chunks of n consecutive elements, oldest first, see Chunks
+    // the last chunk is shorter if the amount of elements is not a multiple of n, n must be larger than 0
+    pub fn chunks(&self, n: usize) -> Chunks<'_, T, S> {
+        assert!(n != 0, "CircularBuffer chunk size must be larger than 0");
+        Chunks::new(self, n)
+    }
+
+    // captures the current position of the elements, so the buffer can be rolled back later with restore()
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            capacity: self.capacity,
+            index_start: self.index_start,
+            index_next_free: self.index_next_free,
+            size: self.size
+        }
+    }
+
+    // rolls the buffer back to the position captured by snapshot(), making the elements of that moment visible again
+    // only the indices are restored, so this is only valid if none of those elements were touched since the snapshot
+    // was taken: writes must not have wrapped around into them (writing within the free space is fine), and they must
+    // not have been read, because reading moves an element out and leaves a default value in its slot
+    // this makes it a good fit for rolling back speculative writes, peeking in between is fine
+    // panics if the capacity changed since the snapshot was taken
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        assert_eq!(self.capacity, snapshot.capacity, "CircularBuffer capacity changed since the snapshot was taken");
+        self.index_start = snapshot.index_start;
+        self.index_next_free = snapshot.index_next_free;
+        self.size = snapshot.size;
+        self.size_increased(0);
+    }
+
+    // returns a reference to the element at a logical index (0 is the oldest element), or None if it's out of bounds
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.size {
+            Some(&self.buffer[self.physical_index(index)])
+        } else {
+            None
+        }
+    }
+
+    // returns a mutable reference to the element at a logical index (0 is the oldest element), or None if it's out of bounds
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.size {
+            let physical_index = self.physical_index(index);
+            Some(&mut self.buffer[physical_index])
+        } else {
+            None
+        }
+    }
+
+    // swaps the elements at two logical indices (0 is the oldest element)
+    // panics if either index is not smaller than the size
+    pub fn swap(&mut self, i: usize, j: usize) {
+        assert!(i < self.size && j < self.size,
+            "CircularBuffer index out of bounds: the size is {} but the indices are {} and {}", self.size, i, j);
+        let (physical_i, physical_j) = (self.physical_index(i), self.physical_index(j));
+        self.buffer.swap(physical_i, physical_j);
+    }
+
+    // removes and returns the element at a logical index by replacing it with the most recently written element
+    // this is O(1), but it does not preserve the order, returns None if the index is out of bounds
+    pub fn swap_remove(&mut self, i: usize) -> Option<T> {
+        if i >= self.size {
+            return None;
+        }
+        let last = self.size - 1;
+        self.swap(i, last);
+        self.pop_back().ok()
+    }
+
+    // returns the amount of elements for which f returns true
+    pub fn count<F: FnMut(&T) -> bool>(&self, mut f: F) -> usize {
+        self.iter().filter(|element| f(element)).count()
+    }
+
+    // returns true if f returns true for at least one element, it stops at the first match
+    pub fn any<F: FnMut(&T) -> bool>(&self, f: F) -> bool {
+        self.iter().any(f)
+    }
+
+    // returns true if f returns true for every element (or if the buffer is empty), it stops at the first mismatch
+    pub fn all<F: FnMut(&T) -> bool>(&self, f: F) -> bool {
+        self.iter().all(f)
+    }
+
+    // returns a new buffer with the same capacity, holding the result of f for every element (oldest first)
+    // this buffer is left unchanged
+    pub fn map_into<U, F>(&self, mut f: F) -> CircularBuffer<U> where F: FnMut(&T) -> U, U: Default + Clone {
+        let mut mapped = CircularBuffer::new(self.capacity);
+        for element in self.iter() {
+            mapped.write(f(element)).unwrap();
+        }
+        mapped
+    }
+
+    // returns the oldest elements that are stored contiguously, up to the end of the internal buffer or the newest
+    // element, whichever comes first, it's the first run of as_slices()
+    // to process all elements one run at a time: handle head_slice(), read (or skip) that many elements, repeat
+    pub fn head_slice(&self) -> &[T] {
+        self.as_slices().0
+    }
+
+    // returns how many elements can be written into the free space after the newest element before wrapping around
+    // to the start of the internal buffer, this is all of the free space unless it wraps, see fill_from()
+    pub fn available_contiguous_write(&self) -> usize {
+        (self.capacity - self.size).min(self.capacity - self.index_next_free)
+    }
+
+    // returns true if the elements are stored as a single run in the internal buffer, so they don't wrap around its
+    // end, an empty buffer is always contiguous, if this returns true as_slices() returns an empty second run,
+    // and peek_chunk() succeeds for every n up to the size
+    pub fn is_contiguous(&self) -> bool {
+        self.index_start + self.size <= self.capacity
+    }
+
+    // returns the elements as (at most) two contiguous runs in the internal buffer, oldest first
+    // the second run is only non-empty if the data wraps around the end of the internal buffer
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        if self.index_start + self.size <= self.capacity {
+            (&self.buffer[self.index_start..self.index_start + self.size], &[])
+        } else {
+            (&self.buffer[self.index_start..], &self.buffer[..self.index_next_free])
+        }
+    }
+
+    // mutable version of as_slices(), so the elements can be processed in place without copying them out
+    // together both slices cover exactly the elements in order, and they borrow the whole buffer mutably
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        if self.index_start + self.size <= self.capacity {
+            (&mut self.buffer[self.index_start..self.index_start + self.size], &mut [])
+        } else {
+            let (wrapped, start) = self.buffer.split_at_mut(self.index_start);
+            (start, &mut wrapped[..self.index_next_free])
+        }
+    }
+
+    // splits the elements into two read-only views, the oldest n elements and the remaining size - n elements
+    // both iterate in order, and nothing is copied, panics if n is larger than the size
+    pub fn split_at(&self, n: usize) -> (Iter<'_, T>, Iter<'_, T>) {
+        assert!(n <= self.size, "CircularBuffer split index out of bounds: the size is {} but the index is {}", self.size, n);
+        let (first, second) = self.as_slices();
+        if n <= first.len() {
+            let (head, rest) = first.split_at(n);
+            (Iter::new(head, &[]), Iter::new(rest, second))
+        } else {
+            let (head, rest) = second.split_at(n - first.len());
+            (Iter::new(first, head), Iter::new(rest, &[]))
+        }
+    }
+
+    // returns a pointer to the first slot of the internal buffer (not to the oldest element), e.g. to hand the buffer to C
+    // the pointer is valid for capacity() elements, as long as the buffer is not modified, moved or dropped
+    // the elements are at the slots index_start..index_start + size, wrapping around at capacity(), see layout(),
+    // the other slots hold leftover or default values and must not be treated as elements
+    pub fn as_ptr(&self) -> *const T {
+        self.buffer.as_ptr()
+    }
+
+    // mutable version of as_ptr(), writing through it doesn't update the indices, so to add or remove elements the
+    // buffer itself must be used, and every slot must always hold a valid T
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.buffer.as_mut_ptr()
+    }
+
+    // returns the internal layout as (index_start, index_next_free, size), for debugging wrap-around issues
+    // or for reconstructing the ring from as_ptr() on the other side of an FFI boundary
+    // this is not part of the normal API, enable the internals feature to use it
+    #[cfg(any(test, feature = "internals"))]
+    pub fn layout(&self) -> (usize, usize, usize) {
+        (self.index_start, self.index_next_free, self.size)
+    }
+
+    // replaces the element at a logical index (0 is the oldest element) and returns the old element
+    // the size and order of the elements don't change, fails if the index is not smaller than the size
+    pub fn set(&mut self, index: usize, value: T) -> Result<T, &'static str> {
+        match self.get_mut(index) {
+            Some(element) => Ok(mem::replace(element, value)),
+            None => Err("CircularBuffer index out of bounds")
+        }
+    }
+
+    // returns an iterator over references to the elements, newest first, it's the same as iter().rev()
+    pub fn iter_rev(&self) -> core::iter::Rev<Iter<'_, T>> {
+        self.iter().rev()
+    }
+
+    // searches a buffer whose elements are sorted (oldest first) for target, like slice::binary_search()
+    // returns Ok with the logical index of a matching element, or Err with the logical index where target could be
+    // inserted to keep the elements sorted, the result is meaningless if the elements are not sorted (see is_sorted())
+    pub fn binary_search(&self, target: &T) -> Result<usize, usize> where T: Ord {
+        // the elements can wrap around the end of the internal buffer, so this works on logical indices
+        let mut low = 0;
+        let mut high = self.size;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.buffer[self.physical_index(mid)].cmp(target) {
+                CmpOrdering::Less => low = mid + 1,
+                CmpOrdering::Greater => high = mid,
+                CmpOrdering::Equal => return Ok(mid)
+            }
+        }
+        Err(low)
+    }
+
+    // returns an iterator over (logical index, reference) pairs, oldest first, it's the same as iter().enumerate()
+    // the logical index counts from the oldest element (0) and is the index that get() and buf[index] accept,
+    // no matter where the element is stored in the internal buffer
+    pub fn indexed_iter(&self) -> core::iter::Enumerate<Iter<'_, T>> {
+        self.iter().enumerate()
+    }
+
+    // returns a new empty buffer with the same capacity and mode (overwrite, growable) as this one,
+    // without copying any elements, the internal buffer is of the same kind (e.g. with the same alignment)
+    // panics if the storage can't create a new internal buffer, which never happens for the default heap storage
+    pub fn clone_empty(&self) -> Self {
+        let storage = self.buffer.reallocated(self.capacity)
+            .unwrap_or_else(|| panic!("CircularBuffer storage can't hold {} elements", self.capacity));
+        let mut result = Self::from_raw_parts(storage, 0);
+        result.policy = self.policy.clone_box();
+        result.growable = self.growable;
+        result.max_capacity = self.max_capacity;
+        result
+    }
+
+    // moves the elements from logical index n onwards into a new buffer and returns it, so the oldest n elements
+    // remain in this buffer, like Vec::split_off()
+    // the new buffer has the same capacity and mode as this one (see clone_empty()), so it can take more elements
+    // panics if n is larger than the size
+    pub fn split_off(&mut self, n: usize) -> Self {
+        assert!(n <= self.size, "CircularBuffer split index out of bounds: the size is {} but the index is {}", self.size, n);
+        let mut tail = self.clone_empty();
+        for i in n..self.size {
+            let index = self.physical_index(i);
+            tail.write(mem::take(&mut self.buffer[index])).expect("the new buffer has the same capacity");
+        }
+        // the moved slots already hold T::default(), so this only moves the tail back
+        self.drop_back_from(n);
+        tail
+    }
+
+    // reads elements from the front as long as f returns true for them, and returns them oldest first
+    // it stops at the first element for which f returns false, that element (and everything after it) stays
+    pub fn drain_while<F: FnMut(&T) -> bool>(&mut self, mut f: F) -> Vec<T> {
+        let mut drained = Vec::new();
+        while !self.is_empty() && f(&self.buffer[self.index_start]) {
+            drained.push(self.read().unwrap());
+        }
+        drained
+    }
+
+    // returns clones of all elements in a new Vec, oldest first, without removing them from the buffer
+    // it's the same as peek_many(size()), but it can't fail
+    pub fn as_vec(&self) -> Vec<T> {
+        self.iter().cloned().collect()
+    }
+
+    // swaps the elements with the elements of other, in order (the oldest element with other[0] and so on),
+    // without cloning anything, e.g. for double buffering between the buffer and a work array
+    // fails (without swapping anything) if the length of other is not the same as the size of the buffer
+    pub fn swap_with_slice(&mut self, other: &mut [T]) -> Result<(), &'static str> {
+        if other.len() != self.size {
+            return Err("CircularBuffer size does not match the length of the slice");
+        }
+        let (first, second) = self.as_mut_slices();
+        let (other_first, other_second) = other.split_at_mut(first.len());
+        first.swap_with_slice(other_first);
+        second.swap_with_slice(other_second);
+        Ok(())
+    }
+
+    // moves all elements into dst, which must be empty, in order and without cloning them, so this buffer is empty
+    // afterwards, dst doesn't need to have the same capacity (or storage), as long as all elements fit
+    // fails (without moving anything) if dst is not empty or doesn't have enough space
+    pub fn move_into<D: Storage<T>>(&mut self, dst: &mut CircularBuffer<T, D>) -> Result<(), &'static str> {
+        if !dst.is_empty() {
+            return Err("CircularBuffer destination is not empty");
+        }
+        dst.grow_to_fit(self.size);
+        if dst.capacity < self.size {
+            return Err("CircularBuffer does not have enough space for the provided elements");
+        }
+
+        for i in 0..self.size {
+            // can't fail, we checked above that all elements fit, so dst can't become full halfway
+            dst.write(self.take_logical(i)).unwrap();
+        }
+        self.skip_front(self.size);
+        Ok(())
+    }
+
+    // returns the size in bytes of a single element, the same as mem::size_of::<T>()
+    pub const fn element_size() -> usize {
+        mem::size_of::<T>()
+    }
+
+    // returns the size in bytes of the internal buffer, which holds capacity slots whether they are in use or not
+    // for the default storage this is the size of the heap allocation, memory that the elements themselves own
+    // (e.g. the contents of a String) is not included
+    pub const fn memory_footprint(&self) -> usize {
+        self.capacity * mem::size_of::<T>()
+    }
+
+    // returns true if every element is smaller than or equal to the next one (oldest first), like slice::is_sorted()
+    // an empty buffer or a buffer with a single element is always sorted
+    pub fn is_sorted(&self) -> bool where T: PartialOrd {
+        self.iter().zip(self.iter().skip(1)).all(|(a, b)| a <= b)
+    }
+
+    // merges the elements of two sorted buffers (see is_sorted()) into a new full buffer, with a capacity of the sum of
+    // both sizes, the elements are cloned, and on equal elements the ones of self come first
+    // the result is only sorted if both buffers are sorted
+    pub fn merge_sorted<O: Storage<T>>(&self, other: &CircularBuffer<T, O>) -> CircularBuffer<T> where T: Ord {
+        let mut merged = Vec::with_capacity(self.size + other.size);
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+            if b < a {
+                merged.push((*b).clone());
+                right.next();
+            } else {
+                merged.push((*a).clone());
+                left.next();
+            }
+        }
+        merged.extend(left.cloned());
+        merged.extend(right.cloned());
+        CircularBuffer::from(merged)
+    }
+
+    // returns true if the oldest prefix.len() elements are equal to prefix, e.g. to check for a frame header
+    // returns false if the buffer holds less elements than the prefix, an empty prefix always matches
+    pub fn starts_with(&self, prefix: &[T]) -> bool where T: PartialEq {
+        prefix.len() <= self.size && self.iter().take(prefix.len()).eq(prefix.iter())
+    }
+
+    // returns true if the newest suffix.len() elements are equal to suffix, e.g. to check for a delimiter
+    // returns false if the buffer holds less elements than the suffix, an empty suffix always matches
+    pub fn ends_with(&self, suffix: &[T]) -> bool where T: PartialEq {
+        suffix.len() <= self.size && self.iter().skip(self.size - suffix.len()).eq(suffix.iter())
+    }
+
+    // private function that increases the index, overflowing if we're going beyond the capacity
+    // this function does not check if the increase makes sense, it's the responsibility of the caller!
+    fn increase_index(&self, index: usize) -> usize {
+        if index == self.capacity - 1 {
+            0
+        } else {
+            index + 1
+        }
+    }
+
+    // private function that creates a buffer around existing storage, where the first `size` slots are in use
+    fn from_raw_parts(buffer: S, size: usize) -> Self {
+        let capacity = buffer.len();
+        Self {
+            capacity,
+            buffer,
+            index_start: 0,
+            index_next_free: if size == capacity { 0 } else { size },
+            size,
+            policy: Box::new(ErrorPolicy),
+            growable: false,
+            max_capacity: None,
+            on_full: None,
+            dropped: 0,
+            high_water_mark: size,
+            #[cfg(feature = "sync")]
+            written: Arc::new(AtomicU64::new(size as u64)),
+            _marker: PhantomData
+        }
+    }
+
+    // private function that must be called whenever the size increased (after updating it), it keeps track of the
+    // high water mark and of the amount of elements written to the tail for subscriptions, amount is 0 when the
+    // elements were added anywhere else
+    #[cfg_attr(not(feature = "sync"), allow(unused_variables))]
+    fn size_increased(&mut self, amount: usize) {
+        self.high_water_mark = self.high_water_mark.max(self.size);
+        #[cfg(feature = "sync")]
+        self.written.fetch_add(amount as u64, Ordering::Release);
+    }
+
+    // private function that doubles the capacity of a growable buffer until the given amount of extra elements fit,
+    // or until the max capacity is reached, it does nothing for regular fixed-capacity buffers
+    // if the storage can't hold the new capacity, the buffer simply stays full
+    fn grow_to_fit(&mut self, additional: usize) {
+        if !self.growable || self.capacity - self.size >= additional {
+            return;
+        }
+        let max_capacity = self.max_capacity.unwrap_or(usize::MAX);
+        let mut new_capacity = self.capacity;
+        while new_capacity - self.size < additional && new_capacity < max_capacity {
+            new_capacity = new_capacity.saturating_mul(2).max(1).min(max_capacity);
+        }
+        if new_capacity != self.capacity {
+            self.reallocate(new_capacity);
+        }
+    }
+
+    // private function that moves the elements into a new internal buffer with the given capacity, starting at index 0
+    // the new buffer is of the same kind as the old one, see Storage::reallocated()
+    // returns false (and leaves the buffer untouched) if the storage can't hold the new capacity
+    // the caller must ensure that the elements fit into the new capacity!
+    fn reallocate(&mut self, new_capacity: usize) -> bool {
+        debug_assert!(self.size <= new_capacity);
+
+        let mut buffer = match self.buffer.reallocated(new_capacity) {
+            Some(buffer) => buffer,
+            None => return false
+        };
+        for (i, value) in self.iter_mut().enumerate() {
+            buffer[i] = mem::take(value);
+        }
+
+        self.buffer = buffer;
+        self.capacity = new_capacity;
+        self.index_start = 0;
+        self.index_next_free = if self.size == new_capacity { 0 } else { self.size };
+        true
+    }
+
+    // private function that returns `len` elements starting at a logical index, borrowed if they are contiguous in
+    // the internal buffer, or cloned if they wrap around its end
+    // the caller must ensure that start + len <= size!
+    fn logical_range(&self, start: usize, len: usize) -> Cow<'_, [T]> {
+        if len == 0 {
+            return Cow::Borrowed(&[]);
+        }
+        let physical_start = self.physical_index(start);
+        if physical_start + len <= self.capacity {
+            Cow::Borrowed(&self.buffer[physical_start..physical_start + len])
+        } else {
+            Cow::Owned((start..start + len).map(|i| self.buffer[self.physical_index(i)].clone()).collect())
+        }
+    }
+
+    // private function that moves the element at a logical index out of the buffer, leaving T::default() behind
+    // the indices are not updated, so the caller must ensure the slot is removed afterwards, see skip_front()
+    fn take_logical(&mut self, logical_index: usize) -> T {
+        let physical_index = self.physical_index(logical_index);
+        mem::take(&mut self.buffer[physical_index])
+    }
+
+    // private function that removes the given amount of elements from the front by only moving the start index
+    // the caller must ensure amount <= size, and that the elements were already taken out (or can be forgotten)
+    fn skip_front(&mut self, amount: usize) {
+        if amount == 0 {
+            return;
+        }
+        self.index_start = self.physical_index(amount);
+        self.size -= amount;
+    }
+
+    // private function that drops the elements from logical index len onwards, so len elements remain
+    fn drop_back_from(&mut self, len: usize) {
+        for i in len..self.size {
+            let index = self.physical_index(i);
+            drop(mem::take(&mut self.buffer[index]));
+        }
+
+        if len != self.size {
+            self.size = len;
+            self.index_next_free = self.physical_index(len);
+        }
+    }
+
+    // private function that copies the indices and the mode of another buffer with the same capacity
+    // the elements themselves must already be in place
+    fn copy_state_from(&mut self, source: &Self) {
+        debug_assert_eq!(self.capacity, source.capacity);
+        self.index_start = source.index_start;
+        self.index_next_free = source.index_next_free;
+        self.size = source.size;
+        self.policy = source.policy.clone_box();
+        self.growable = source.growable;
+        self.max_capacity = source.max_capacity;
+        self.dropped = source.dropped;
+        self.high_water_mark = source.high_water_mark;
+    }
+
+    // private function that maps a logical index (0 is the oldest element) to an index in the internal buffer
+    // this function does not check if the index is within the size, it's the responsibility of the caller!
+    fn physical_index(&self, logical_index: usize) -> usize {
+        (self.index_start + logical_index) % self.capacity
+    }
+
+    // private function that decreases the index, wrapping to the end if we're going below zero
+    // this function does not check if the decrease makes sense, it's the responsibility of the caller!
+    fn decrease_index(&self, index: usize) -> usize {
+        if index == 0 {
+            self.capacity - 1
+        } else {
+            index - 1
+        }
+    }
+
+}
+
+#[cfg(feature = "sync")]
+impl<T: Default + Clone, S: Storage<T>> CircularBuffer<T, S> {
+
+    // returns a subscription that can pull every element written to the buffer from now on,
+    // without consuming it for the buffer itself or for other subscribers, see Subscription
+    pub fn subscribe(&mut self) -> Subscription<T> {
+        Subscription::new(Arc::downgrade(&self.written), self.written())
+    }
+
+    pub(crate) fn written(&self) -> u64 {
+        self.written.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn written_ptr(&self) -> *const AtomicU64 {
+        Arc::as_ptr(&self.written)
+    }
+
+}
+
+impl<U: Default + Clone, S: Storage<Vec<U>>> CircularBuffer<Vec<U>, S> {
+
+    // concatenates all inner vecs (oldest first) into a new, full buffer with a capacity equal to the total amount of elements
+    // the elements are moved, not cloned
+    pub fn flatten(self) -> CircularBuffer<U> {
+        let values: Vec<U> = self.into_iter().flatten().collect();
+        CircularBuffer::from(values)
+    }
+
+}
+
+impl<T: ToString, S: Storage<T>> CircularBuffer<T, S> {
+
+    pub fn print(&self) {
+        println!("{}", self);
+    }
+
+    // renders every slot of the internal buffer, like to_string(), but with a custom separator between the slots
+    // and a custom placeholder for the unused slots, e.g. to_string_with("|", "-") gives "[1|2|-|-]"
+    pub fn to_string_with(&self, separator: &str, empty: &str) -> String {
+        let mut output: String = String::from("[");
+
+        for i in 0 .. self.capacity {
+            // a slot is live if its distance from the oldest element is within the size, which also covers the
+            // case where the elements wrap around the end of the internal buffer
+            let offset = (i + self.capacity - self.index_start) % self.capacity;
+            if offset < self.size {
+                output += self.buffer[i].to_string().as_str();
+            } else {
+                output += empty;
+            }
+            if i != self.capacity-1 {
+                output += separator;
+            }
+        }
+        output += "]";
+        output
+    }
+
+}
+
+// creates a buffer with a capacity of 0, which can't hold any elements (and doesn't allocate)
+// this is mostly useful as a placeholder in structs that derive Default, use with_capacity() for an actual buffer
+impl<T: Default + Clone> Default for CircularBuffer<T> {
+    fn default() -> Self {
+        Self::with_capacity(0)
+    }
+}
+
+// creates a full buffer with a capacity equal to the length of the vec, reusing its allocation where possible
+impl<T: Default + Clone> From<Vec<T>> for CircularBuffer<T> {
+    fn from(values: Vec<T>) -> Self {
+        let size = values.len();
+        Self::from_raw_parts(values.into_boxed_slice().into(), size)
+    }
+}
+
+// creates a full buffer with a capacity equal to the length of the slice, cloning the elements
+impl<T: Default + Clone> From<&[T]> for CircularBuffer<T> {
+    fn from(values: &[T]) -> Self {
+        Self::from(values.to_vec())
+    }
+}
+
+// moves the elements into a deque, the oldest element ends up at the front, see also from_vec_deque()
+impl<T: Default + Clone, S: Storage<T>> From<CircularBuffer<T, S>> for VecDeque<T> {
+    fn from(buffer: CircularBuffer<T, S>) -> Self {
+        buffer.into_iter().collect()
+    }
+}
+
+// indexes by logical position, so buf[0] is the oldest element, panics if the index is not smaller than the size
+// use get() for a non-panicking alternative
+impl<T: Default + Clone, S: Storage<T>> Index<usize> for CircularBuffer<T, S> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let size = self.size;
+        match self.get(index) {
+            Some(element) => element,
+            None => panic!("CircularBuffer index out of bounds: the size is {} but the index is {}", size, index)
+        }
+    }
+}
+
+// indexes by logical position, so buf[0] is the oldest element, panics if the index is not smaller than the size
+// use get_mut() for a non-panicking alternative
+impl<T: Default + Clone, S: Storage<T>> IndexMut<usize> for CircularBuffer<T, S> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let size = self.size;
+        match self.get_mut(index) {
+            Some(element) => element,
+            None => panic!("CircularBuffer index out of bounds: the size is {} but the index is {}", size, index)
+        }
+    }
+}
+
+// two buffers are equal if and only if they hold the same amount of elements, and the elements are pairwise equal in
+// FIFO order (oldest first), so the capacity and the internal layout (where the elements start, and whether they wrap
+// around the end of the internal buffer) don't matter, the same holds for Eq, Ord and Hash
+impl<T: Default + Clone + PartialEq, S: Storage<T>> PartialEq for CircularBuffer<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.iter().eq(other.iter())
+    }
+}
+
+// PartialEq is an equivalence relation whenever T: Eq, see PartialEq above for what is compared
+impl<T: Default + Clone + Eq, S: Storage<T>> Eq for CircularBuffer<T, S> {}
+
+// compares the elements in order lexicographically, like slices, regardless of capacity or internal offset
+// so an empty buffer comes first, and a buffer comes before any longer buffer that starts with the same elements
+impl<T: Default + Clone + PartialOrd, S: Storage<T>> PartialOrd for CircularBuffer<T, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Default + Clone + Ord, S: Storage<T>> Ord for CircularBuffer<T, S> {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+// hashes the size followed by the elements in order, so it's consistent with PartialEq
+// (equal buffers have equal hashes, regardless of capacity or internal offset)
+impl<T: Default + Clone + Hash, S: Storage<T>> Hash for CircularBuffer<T, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.size.hash(state);
+        for element in self.iter() {
+            element.hash(state);
+        }
+    }
+}
+
+// the clone has the same capacity, alignment and mode, but not the on_full() callback (which can't be cloned),
+// and subscriptions of the original don't follow the writes to the clone
+impl<T: Default + Clone, S: Storage<T> + Clone> Clone for CircularBuffer<T, S> {
+    fn clone(&self) -> Self {
+        let mut result = Self::from_raw_parts(self.buffer.clone(), 0);
+        result.copy_state_from(self);
+        result
+    }
+
+    // reuses the internal buffer of self if both buffers have the same capacity, instead of allocating a new one
+    // the old elements of self are dropped first
+    fn clone_from(&mut self, source: &Self) {
+        if self.capacity != source.capacity {
+            *self = source.clone();
+            return;
+        }
+        self.reset();
+        for i in 0..source.size {
+            let index = source.physical_index(i);
+            self.buffer[index] = source.buffer[index].clone();
+        }
+        self.copy_state_from(source);
+    }
+}
+
+// compares the elements in order against a slice, without allocating, e.g. assert_eq!(buf, [1, 2, 3][..])
+impl<T: Default + Clone + PartialEq, S: Storage<T>> PartialEq<[T]> for CircularBuffer<T, S> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.size == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<'a, T: Default + Clone + PartialEq, S: Storage<T>> PartialEq<&'a [T]> for CircularBuffer<T, S> {
+    fn eq(&self, other: &&'a [T]) -> bool {
+        *self == **other
+    }
+}
+
+impl<T: Default + Clone + PartialEq, S: Storage<T>> PartialEq<Vec<T>> for CircularBuffer<T, S> {
+    fn eq(&self, other: &Vec<T>) -> bool {
+        *self == other[..]
+    }
+}
+
+// formats the elements in order like a list, e.g. [1, 2, 3], so buffers can be used in assert_eq!()
+// unlike Display, this doesn't show the unused slots of the internal buffer
+impl<T: Default + Clone + fmt::Debug, S: Storage<T>> fmt::Debug for CircularBuffer<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: ToString, S: Storage<T>> fmt::Display for CircularBuffer<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_with(",", "_"))
+    }
+}
+
+impl<S: Storage<u8>> CircularBuffer<u8, S> {
+
+    // reads one length-prefixed frame (header + payload) if it is completely available
+    // the payload length is stored big-endian in the header, from byte offset len_at up to the end of the header
+    // returns Ok(None) and leaves the buffer untouched if the frame hasn't been fully received yet
+    pub fn read_frame(&mut self, header_len: usize, len_at: usize) -> Result<Option<Vec<u8>>, &'static str> {
+        if len_at >= header_len || header_len - len_at > mem::size_of::<u64>() {
+            return Err("CircularBuffer frame length field does not fit inside the header");
+        }
+
+        if self.size < header_len {
+            return Ok(None);
+        }
+
+        let mut payload_len: u64 = 0;
+        for i in len_at..header_len {
+            payload_len = (payload_len << 8) | u64::from(self.buffer[self.physical_index(i)]);
+        }
+
+        let available = (self.size - header_len) as u64;
+        if payload_len > available {
+            return Ok(None);
+        }
+
+        self.read_many(header_len + payload_len as usize).map(Some)
+    }
+
+    // writes all bytes to writer, oldest first, straight from the internal buffer, and returns how many were written
+    // the bytes are removed as soon as the writer accepts them, so if it fails halfway, the bytes that were
+    // written are gone and the rest stays in the buffer
+    // this can't use io::Write::write_all(), because when that fails it doesn't tell how much it already wrote
+    pub fn flush_to<W: io::Write>(&mut self, writer: &mut W) -> io::Result<usize> {
+        let mut flushed = 0;
+        while !self.is_empty() {
+            let (first, _) = self.as_slices();
+            match writer.write(first) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write the buffered bytes")),
+                Ok(written) => {
+                    assert!(written <= first.len(), "CircularBuffer writer accepted more bytes than it was given");
+                    self.skip_front(written);
+                    flushed += written;
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {},
+                Err(error) => return Err(error)
+            }
+        }
+        Ok(flushed)
+    }
+
+    // reads bytes from reader straight into the free space of the internal buffer, and returns how many were read
+    // this does a single (vectored) read, so like io::Read::read() it can read less than the free space,
+    // returns 0 when the reader is at its end or when the buffer is full
+    pub fn fill_from<R: io::Read>(&mut self, reader: &mut R) -> io::Result<usize> {
+        self.fill_from_limited(reader, usize::MAX)
+    }
+
+    // same as fill_from(), but it reads at most limit bytes, even if there is more free space, e.g. to read a protocol
+    // one fixed-size chunk at a time, returns 0 when the reader is at its end, the buffer is full or limit is 0
+    pub fn fill_from_limited<R: io::Read>(&mut self, reader: &mut R, limit: usize) -> io::Result<usize> {
+        let free = (self.capacity - self.size).min(limit);
+        if free == 0 {
+            return Ok(0);
+        }
+
+        // the free space starts at the tail, and wraps around to the start of the internal buffer if needed
+        let start = self.index_next_free;
+        let first_len = self.available_contiguous_write().min(free);
+        let (wrapped, tail) = self.buffer.split_at_mut(start);
+        let mut runs = [io::IoSliceMut::new(&mut tail[..first_len]), io::IoSliceMut::new(&mut wrapped[..free - first_len])];
+
+        let read = loop {
+            match reader.read_vectored(&mut runs) {
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {},
+                result => break result?
+            }
+        };
+        assert!(read <= free, "CircularBuffer reader returned more bytes than it was given room for");
+
+        self.index_next_free = (start + read) % self.capacity;
+        self.size += read;
+        self.size_increased(read);
+        Ok(read)
+    }
+
+    // returns an iterator over all complete records of record_size bytes, oldest first
+    // a trailing partial record (one that hasn't been fully written yet) is not included
+    pub fn records(&self, record_size: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+        assert!(record_size != 0, "CircularBuffer record size must be larger than 0");
+        let count = self.size / record_size;
+        (0..count).map(move |record| {
+            let start = record * record_size;
+            (start..start + record_size).map(|i| self.buffer[self.physical_index(i)]).collect()
+        })
+    }
+
+}
+
+#[cfg(feature = "checksum")]
+impl<S: Storage<u8>> CircularBuffer<u8, S> {
+
+    // folds a keyed FNV-1a style hash over the bytes in FIFO order, mixing in the position of every byte
+    // this is order sensitive, so reordering the same bytes results in a different value
+    // note that this is meant for cheap tamper evidence, it is NOT a cryptographic hash
+    pub fn rolling_hash_chain(&self) -> u64 {
+        const KEY: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = KEY;
+        let mut index = self.index_start;
+        for position in 0..self.size {
+            hash ^= u64::from(self.buffer[index]) ^ ((position as u64) << 8);
+            hash = hash.wrapping_mul(PRIME);
+            index = self.increase_index(index);
+        }
+        hash
+    }
+
+}
+
+#[cfg(test)]
+// the original tests compare with bool literals and index in loops, they are kept as they were written
+#[allow(clippy::bool_assert_comparison, clippy::needless_range_loop)]
+mod tests {
+
+    use crate::{CircularBuffer, DiffKind};
+    use rand::distributions::{Distribution, Uniform};
     use std::ops::Deref;
 
     #[test]
-    fn test_basic_write() {
+    fn test_basic_write() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+
+        let write_result = buf.write(1);
+        assert!(write_result.is_ok());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 1);
+
+        assert_eq!(buf.index_start, 0);
+        assert_eq!(buf.index_next_free, 1);
+
+        assert_eq!(buf.buffer[0], 1);
+
+    }
+
+    #[test]
+    fn test_basic_read() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+
+        let write_result = buf.write(1);
+        assert!(write_result.is_ok());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 1);
+
+        let read_result = buf.read();
+        assert!(read_result.is_ok());
+        assert!(buf.is_empty());
+        assert_eq!(buf.size(), 0);
+        assert_eq!(read_result.unwrap(), 1);
+
+    }
+
+    #[test]
+    fn test_basic_peek() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+
+        let write_result = buf.write(1);
+        assert!(write_result.is_ok());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 1);
+
+        let peek_result = buf.peek();
+        assert!(peek_result.is_ok());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 1);
+        assert_eq!(*peek_result.unwrap(), 1);
+
+        let read_result = buf.read();
+        assert!(read_result.is_ok());
+        assert!(buf.is_empty());
+        assert_eq!(buf.size(), 0);
+        assert_eq!(read_result.unwrap(), 1);
+
+    }
+
+    #[test]
+    fn test_basic_peek_many() {
+
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2,3]).unwrap();
+
+        // can't read 4
+        let mut peek_result = buf.peek_many(4);
+        assert!(peek_result.is_err());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 3);
+
+        peek_result = buf.peek_many(2);
+        assert!(peek_result.is_ok());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 3);
+
+        let values = peek_result.unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0], 1);
+        assert_eq!(values[1], 2);
+
+    }
+
+    #[test]
+    fn test_basic_write_many() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+
+        let mut write_result = buf.write_many(&[1,2,3]);
+        assert!(write_result.is_ok());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 3);
+
+        let mut read_result = buf.read();
+        assert!(read_result.is_ok());
+        assert_eq!(buf.size(), 2);
+        assert_eq!(read_result.unwrap(), 1);
+
+        read_result = buf.read();
+        assert!(read_result.is_ok());
+        assert_eq!(buf.size(), 1);
+        assert_eq!(read_result.unwrap(), 2);
+
+        read_result = buf.read();
+        assert!(read_result.is_ok());
+        assert_eq!(buf.size(), 0);
+        assert_eq!(read_result.unwrap(), 3);
+
+        assert!(buf.is_empty());
+
+        // buffer is now empty, try to add more that allowed
+        write_result = buf.write_many(&[1,2,3,4,5]);
+        assert!(write_result.is_err());
+        assert!(buf.is_empty());
+        assert_eq!(buf.size(), 0);
+
+    }
+
+    #[test]
+    fn test_write_many_tracked() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read_many(2).unwrap();
+
+        // buffer now holds [3], the new batch wraps around the end of the internal buffer
+        let range = buf.write_many_tracked(&[4,5,6]).unwrap();
+        assert_eq!(range, 1..4);
+        let values = buf.peek_many(range.end).unwrap();
+        assert_eq!(&values[range], &[4,5,6]);
+
+        // not enough space, nothing gets written
+        assert!(buf.write_many_tracked(&[7]).is_err());
+        assert_eq!(buf.size(), 4);
+
+        // the overflow policy doesn't apply, the batch is never written partially
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.set_overflow_policy(crate::DropNewest);
+        buf.write_many(&[1,2]).unwrap();
+        assert!(buf.write_many_tracked(&[3,4,5]).is_err());
+        assert_eq!(buf, vec![1,2]);
+        assert_eq!(buf.dropped_count(), 0);
+        assert_eq!(buf.write_many_tracked(&[3,4]).unwrap(), 2..4);
+    }
+
+    #[test]
+    fn test_basic_read_many() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2,3]).unwrap();
+
+        // can't read 4
+        let mut read_result = buf.read_many(4);
+        assert!(read_result.is_err());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 3);
+
+        read_result = buf.read_many(2);
+        assert!(read_result.is_ok());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 1);
+        let boxed_values = read_result.unwrap();
+        let values = boxed_values.deref();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0], 1);
+        assert_eq!(values[1], 2);
+    }
+
+    #[test]
+    fn test_basic_clear() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+
+        let write_result = buf.write_many(&[1,2,3]);
+        assert!(write_result.is_ok());
+        assert_eq!(buf.is_empty(), false);
+        assert_eq!(buf.size(), 3);
+
+        buf.clear();
+        assert!(buf.is_empty());
+        assert_eq!(buf.size(), 0);
+
+    }
+
+    #[test]
+    fn test_clear_fast() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read().unwrap();
+        buf.write_many(&[4,5]).unwrap();
+
+        buf.clear_fast();
+        assert!(buf.is_empty());
+        assert_eq!(buf.size(), 0);
+        assert_eq!(buf.index_start, 0);
+        assert_eq!(buf.index_next_free, 0);
+
+        buf.write_many(&[6,7,8,9]).unwrap();
+        assert_eq!(buf.read_many(4).unwrap(), vec![6,7,8,9]);
+    }
+
+    #[test]
+    fn test_clamp_all() {
+        let mut buf = CircularBuffer::<i32>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+
+        // the elements now wrap around the end of the internal buffer
+        buf.write_many(&[-5,3,12,7]).unwrap();
+        buf.clamp_all(0, 10);
+        assert_eq!(buf.peek_many(4).unwrap(), vec![0,3,10,7]);
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        assert!(!buf.contains(&0));
+
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write_many(&[4,5]).unwrap();
+
+        // the stale slots still hold the default value, which must not count as a match
+        assert!(buf.contains(&3));
+        assert!(buf.contains(&5));
+        assert!(!buf.contains(&1));
+        assert!(!buf.contains(&0));
+    }
+
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn test_rolling_hash_chain() {
+        let mut a = CircularBuffer::<u8>::new(4);
+        a.write_many(&[1,2,3]).unwrap();
+
+        let mut b = CircularBuffer::<u8>::new(4);
+        b.write_many(&[3,2,1]).unwrap();
+        assert_ne!(a.rolling_hash_chain(), b.rolling_hash_chain());
+
+        // same contents, but wrapped around the end of the internal buffer
+        let mut c = CircularBuffer::<u8>::new(4);
+        c.write_many(&[9,9,9]).unwrap();
+        c.read_many(3).unwrap();
+        c.write_many(&[1,2,3]).unwrap();
+        assert_eq!(a.rolling_hash_chain(), c.rolling_hash_chain());
+    }
+
+    #[test]
+    fn test_from_vec_and_slice() {
+        let mut buf = CircularBuffer::from(vec![1,2,3]);
+        assert!(buf.is_full());
+        assert_eq!(buf.capacity(), 3);
+        assert_eq!(buf.index_start, 0);
+        assert_eq!(buf.index_next_free, 0);
+        assert_eq!(buf.read().unwrap(), 1);
+        buf.write(4).unwrap();
+        assert_eq!(buf.read_many(3).unwrap(), vec![2,3,4]);
+
+        let buf = CircularBuffer::from(&[5,6][..]);
+        assert!(buf.is_full());
+        assert_eq!(buf.capacity(), 2);
+        assert_eq!(buf.peek_many(2).unwrap(), vec![5,6]);
+    }
+
+    #[test]
+    fn test_unique() {
+        let mut buf = CircularBuffer::<u8>::new(6);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(4).unwrap();
+
+        // the elements now wrap around the end of the internal buffer
+        buf.write_many(&[3,1,3,2,1,3]).unwrap();
+        assert_eq!(buf.unique(), vec![3,1,2]);
+
+        buf.clear();
+        assert!(buf.unique().is_empty());
+    }
+
+    #[test]
+    fn test_push_front() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[2,3]).unwrap();
+
+        // the head wraps from 0 to the end of the internal buffer
+        buf.push_front(1).unwrap();
+        assert_eq!(buf.index_start, 3);
+        assert_eq!(buf.size(), 3);
+        assert_eq!(*buf.peek().unwrap(), 1);
+
+        buf.push_front(0).unwrap();
+        assert!(buf.is_full());
+        assert!(buf.push_front(9).is_err());
+        assert_eq!(buf.read_many(4).unwrap(), vec![0,1,2,3]);
+    }
+
+    #[test]
+    fn test_read_frame() {
+        let mut buf = CircularBuffer::<u8>::new(8);
+
+        // not even a complete header
+        buf.write_many(&[0xAA]).unwrap();
+        assert_eq!(buf.read_frame(3, 1).unwrap(), None);
+
+        // header says 3 bytes of payload, but only 2 have arrived
+        buf.write_many(&[0x00, 0x03, 1, 2]).unwrap();
+        assert_eq!(buf.read_frame(3, 1).unwrap(), None);
+        assert_eq!(buf.size(), 5);
+
+        buf.write(3).unwrap();
+        assert_eq!(buf.read_frame(3, 1).unwrap(), Some(vec![0xAA, 0x00, 0x03, 1, 2, 3]));
+        assert!(buf.is_empty());
+
+        // a frame that wraps around the end of the internal buffer, followed by the start of the next one
+        buf.write_many(&[0xBB, 0x00, 0x02, 4, 5, 0xCC]).unwrap();
+        assert_eq!(buf.read_frame(3, 1).unwrap(), Some(vec![0xBB, 0x00, 0x02, 4, 5]));
+        assert_eq!(buf.size(), 1);
+
+        assert!(buf.read_frame(3, 3).is_err());
+    }
+
+    #[test]
+    fn test_pop_back() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        assert!(buf.pop_back().is_err());
+
+        buf.write_many(&[1,2,3,4]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write(5).unwrap();
+
+        // the tail wraps back from 0 to the end of the internal buffer
+        assert_eq!(buf.pop_back().unwrap(), 5);
+        assert_eq!(buf.index_next_free, 0);
+        assert_eq!(buf.pop_back().unwrap(), 4);
+        assert_eq!(buf.index_next_free, 3);
+        assert_eq!(buf.size(), 1);
+        assert_eq!(buf.read().unwrap(), 3);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_unchecked() {
+        let mut checked = CircularBuffer::<u8>::new(4);
+        let mut unchecked = CircularBuffer::<u8>::new(4);
+
+        // wrap around a couple of times, only touching the unchecked methods when it's known to be safe
+        for i in 0..10 {
+            if !unchecked.is_full() {
+                checked.write(i).unwrap();
+                unsafe { unchecked.write_unchecked(i) };
+            }
+            if i % 2 == 0 {
+                let expected = *checked.peek().unwrap();
+                assert!(!unchecked.is_empty());
+                assert_eq!(unsafe { *unchecked.peek_unchecked() }, expected);
+                assert_eq!(unsafe { unchecked.read_unchecked() }, checked.read().unwrap());
+            }
+            assert_eq!(unchecked.index_start, checked.index_start);
+            assert_eq!(unchecked.index_next_free, checked.index_next_free);
+            assert_eq!(unchecked.size(), checked.size());
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "CircularBuffer is empty")]
+    fn test_unchecked_debug_empty() {
+        let mut buf = CircularBuffer::<u8>::new(2);
+        unsafe { buf.read_unchecked() };
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "CircularBuffer is full")]
+    fn test_unchecked_debug_full() {
+        let mut buf = CircularBuffer::<u8>::new(1);
+        buf.write(1).unwrap();
+        unsafe { buf.write_unchecked(2) };
+    }
+
+    #[test]
+    fn test_peek_back() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        assert!(buf.peek_back().is_err());
+
+        buf.write_many(&[1,2,3,4]).unwrap();
+        assert_eq!(*buf.peek_back().unwrap(), 4);
+
+        // index_next_free wrapped to 0, so the newest element is at the end of the internal buffer
+        assert_eq!(buf.index_next_free, 0);
+        buf.read().unwrap();
+        assert_eq!(*buf.peek_back().unwrap(), 4);
+
+        buf.write(5).unwrap();
+        assert_eq!(*buf.peek_back().unwrap(), 5);
+        assert_eq!(buf.size(), 4);
+    }
+
+    #[test]
+    fn test_balance() {
+        let mut a = CircularBuffer::<u8>::new(8);
+        let mut b = CircularBuffer::<u8>::new(8);
+        a.write_many(&[1,2,3,4,5,6]).unwrap();
+        b.write(7).unwrap();
+
+        assert_eq!(CircularBuffer::balance(&mut a, &mut b), 2);
+        assert_eq!(a.peek_many(4).unwrap(), vec![3,4,5,6]);
+        assert_eq!(b.peek_many(3).unwrap(), vec![7,1,2]);
+
+        // works in the other direction too, and respects the capacity of the receiving buffer
+        let mut small = CircularBuffer::<u8>::new(2);
+        let mut large = CircularBuffer::<u8>::new(8);
+        large.write_many(&[1,2,3,4,5,6,7,8]).unwrap();
+        assert_eq!(CircularBuffer::balance(&mut small, &mut large), 2);
+        assert!(small.is_full());
+        assert_eq!(small.peek_many(2).unwrap(), vec![1,2]);
+        assert_eq!(large.size(), 6);
+
+        // already balanced
+        assert_eq!(CircularBuffer::balance(&mut a, &mut b), 0);
+    }
+
+    #[test]
+    fn test_peek_mut() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        assert!(buf.peek_mut().is_none());
+
+        buf.write_many(&[1,2]).unwrap();
+        *buf.peek_mut().unwrap() += 10;
+        *buf.front_mut().unwrap() += 10;
+        assert_eq!(buf.size(), 2);
+        assert_eq!(buf.read_many(2).unwrap(), vec![21,2]);
+        assert!(buf.front_mut().is_none());
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut a = CircularBuffer::<u8>::new(4);
+        a.write_many(&[9,9,9]).unwrap();
+        a.read_many(3).unwrap();
+        a.write_many(&[1,2,3]).unwrap();
+
+        let mut b = CircularBuffer::<u8>::new(6);
+        b.write_many(&[1,5,3]).unwrap();
+
+        // equal size, one changed element
+        assert_eq!(a.diff(&b), vec![(1, DiffKind::Changed { from: 2, to: 5 })]);
+        assert!(a.diff(&a).is_empty());
+
+        // unequal size
+        b.write_many(&[4,6]).unwrap();
+        assert_eq!(a.diff(&b), vec![
+            (1, DiffKind::Changed { from: 2, to: 5 }),
+            (3, DiffKind::OnlyInOther(4)),
+            (4, DiffKind::OnlyInOther(6))
+        ]);
+        assert_eq!(b.diff(&a)[1], (3, DiffKind::OnlyInSelf(4)));
+    }
+
+    #[test]
+    fn test_into_iterator() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write_many(&[4,5,6]).unwrap();
+
+        // borrowed, across the wrap boundary
+        let mut values = Vec::new();
+        for value in &buf {
+            values.push(*value);
+        }
+        assert_eq!(values, vec![3,4,5,6]);
+        assert_eq!(buf.iter().len(), 4);
+        assert_eq!(buf.iter().rev().copied().collect::<Vec<u8>>(), vec![6,5,4,3]);
+
+        // mutably borrowed
+        for value in &mut buf {
+            *value *= 2;
+        }
+        assert_eq!(buf.peek_many(4).unwrap(), vec![6,8,10,12]);
+
+        // owned
+        let values: Vec<u8> = buf.into_iter().collect();
+        assert_eq!(values, vec![6,8,10,12]);
+
+        let empty = CircularBuffer::<u8>::new(4);
+        assert_eq!(empty.iter().next(), None);
+    }
+
+    #[test]
+    fn test_sliding_apply() {
+        let mut buf = CircularBuffer::<u32>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4,5]).unwrap();
+
+        // moving sum over a window of 3, on a buffer that wraps around
+        let mut out = CircularBuffer::<u32>::new(3);
+        buf.sliding_apply(3, |window| window.iter().sum(), &mut out).unwrap();
+        assert_eq!(out.peek_many(3).unwrap(), vec![6,9,12]);
+
+        // not enough space for the results
+        let mut small = CircularBuffer::<u32>::new(2);
+        assert!(buf.sliding_apply(3, |window| window.iter().sum(), &mut small).is_err());
+        assert!(small.is_empty());
+
+        // window larger than the amount of elements produces nothing
+        buf.sliding_apply(6, |window| window.iter().sum(), &mut small).unwrap();
+        assert!(small.is_empty());
+        assert!(buf.sliding_apply(0, |window| window.iter().sum(), &mut small).is_err());
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut buf = CircularBuffer::<f32>::new(4);
+        buf.write_many(&[0.0,0.0,1.0]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write_many(&[2.0,3.0]).unwrap();
+
+        let iter = buf.iter_mut();
+        assert_eq!(iter.len(), 3);
+        for sample in iter {
+            *sample *= 0.5;
+        }
+        assert_eq!(buf.peek_many(3).unwrap(), vec![0.5,1.0,1.5]);
+
+        // the free slot must not have been touched
+        assert_eq!(buf.buffer[1], 0.0);
+    }
+
+    #[test]
+    fn test_records() {
+        let mut buf = CircularBuffer::<u8>::new(8);
+        buf.write_many(&[0,0,0,0,0]).unwrap();
+        buf.read_many(5).unwrap();
+
+        // the second record straddles the end of the internal buffer, the last one is incomplete
+        buf.write_many(&[1,2,3,4,5,6,7]).unwrap();
+        let records: Vec<Vec<u8>> = buf.records(3).collect();
+        assert_eq!(records, vec![vec![1,2,3], vec![4,5,6]]);
+        assert_eq!(buf.size(), 7);
+
+        assert_eq!(buf.records(8).count(), 0);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut buf = CircularBuffer::<u8>::new(6);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(4).unwrap();
+        buf.write_many(&[1,2,3,4,5,6]).unwrap();
+
+        buf.retain(|value| value % 2 == 0);
+        assert_eq!(buf.size(), 3);
+        assert_eq!(buf.peek_many(3).unwrap(), vec![2,4,6]);
+        assert_eq!(buf.index_next_free, 1);
+
+        // the freed up space can be written again
+        buf.write_many(&[7,8,9]).unwrap();
+        assert_eq!(buf.read_many(6).unwrap(), vec![2,4,6,7,8,9]);
+
+        buf.retain(|_| false);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_run_length_encode() {
+        let mut buf = CircularBuffer::<u8>::new(8);
+        assert!(buf.run_length_encode().is_empty());
+
+        buf.write_many(&[0,0,0,0,0]).unwrap();
+        buf.read_many(5).unwrap();
+
+        // the run of 2s straddles the end of the internal buffer
+        buf.write_many(&[1,2,2,2,2,3,1,1]).unwrap();
+        assert_eq!(buf.run_length_encode(), vec![(1,1), (2,4), (3,1), (1,2)]);
+    }
+
+    #[test]
+    fn test_numeric_statistics() {
+        let mut buf = CircularBuffer::<u32>::new(4);
+        assert_eq!(buf.sum(), 0);
+        assert_eq!(buf.min_element(), None);
+        assert_eq!(buf.max_element(), None);
+        assert_eq!(buf.mean(), None);
+
+        buf.write_many(&[100,100,100]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[4,1,7,8]).unwrap();
+
+        // the stale slots contain 100, which must not be part of the results
+        assert_eq!(buf.sum(), 20);
+        assert_eq!(buf.min_element(), Some(1));
+        assert_eq!(buf.max_element(), Some(8));
+        assert_eq!(buf.mean(), Some(5.0));
+
+        let mut floats = CircularBuffer::<f32>::new(2);
+        floats.write_many(&[0.5, 1.5]).unwrap();
+        assert_eq!(floats.sum(), 2.0);
+        assert_eq!(floats.mean(), Some(1.0));
+
+        // 64-bit integers don't convert to f64 losslessly, so they need an explicit conversion
+        let mut wide = CircularBuffer::<i64>::new(3);
+        assert_eq!(wide.mean_by(|value| *value as f64), None);
+        wide.write_many(&[-4, 10, 3]).unwrap();
+        assert_eq!(wide.mean_by(|value| *value as f64), Some(3.0));
+    }
+
+    #[test]
+    fn test_from_run_length() {
+        let mut buf = CircularBuffer::<u8>::new(6);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(4).unwrap();
+        buf.write_many(&[1,1,1,2,3,3]).unwrap();
+
+        let runs = buf.run_length_encode();
+        let decoded = CircularBuffer::from_run_length(6, &runs).unwrap();
+        assert_eq!(decoded.capacity(), 6);
+        assert_eq!(decoded.peek_many(6).unwrap(), buf.peek_many(6).unwrap());
+
+        assert!(CircularBuffer::from_run_length(5, &runs).is_err());
+        assert!(CircularBuffer::from_run_length(5, &[(1u8, usize::MAX), (2, 1)]).is_err());
+    }
+
+    #[test]
+    fn test_write_overwrite() {
+        let mut buf = CircularBuffer::<u8>::new(3);
+        assert_eq!(buf.write_overwrite(1), None);
+        assert_eq!(buf.write_overwrite(2), None);
+        assert_eq!(buf.write_overwrite(3), None);
+        assert_eq!(buf.write_overwrite(4), Some(1));
+        assert_eq!(buf.peek_many(3).unwrap(), vec![2,3,4]);
+
+        let mut evicted = Vec::new();
+        buf.read().unwrap();
+        buf.write_overwrite_with(5, |value| evicted.push(value));
+        assert!(evicted.is_empty());
+        buf.write_overwrite_with(6, |value| evicted.push(value));
+        buf.write_overwrite_with(7, |value| evicted.push(value));
+        assert_eq!(evicted, vec![3,4]);
+        assert_eq!(buf.peek_many(3).unwrap(), vec![5,6,7]);
+    }
+
+    #[test]
+    fn test_compare_and_replace_front() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        assert!(buf.compare_and_replace_front(&1, 2).is_err());
+
+        buf.write_many(&[1,2]).unwrap();
+        assert_eq!(buf.compare_and_replace_front(&5, 9).unwrap(), false);
+        assert_eq!(*buf.peek().unwrap(), 1);
+
+        assert_eq!(buf.compare_and_replace_front(&1, 9).unwrap(), true);
+        assert_eq!(buf.peek_many(2).unwrap(), vec![9,2]);
+    }
+
+    #[test]
+    fn test_new_aligned() {
+        use std::rc::Rc;
+
+        let mut buf = CircularBuffer::<u8>::new_aligned(10, 64);
+        assert_eq!(buf.capacity(), 64);
+        assert_eq!(buf.buffer.as_ptr() as usize % 64, 0);
+
+        // a new capacity keeps the alignment of the start address, but it isn't rounded up
+        buf.resize_keep_newest(100);
+        assert_eq!(buf.capacity(), 100);
+        assert_eq!(buf.buffer.as_ptr() as usize % 64, 0);
+
+        let mut buf = CircularBuffer::<u32>::new_aligned(20, 32);
+        assert_eq!(buf.capacity(), 24);
+        assert_eq!(buf.buffer.as_ptr() as usize % 32, 0);
+        assert_eq!(buf.buffer.len() * 4 % 32, 0);
+
+        buf.write_many(&[1,2,3]).unwrap();
+        assert_eq!(buf.read_many(3).unwrap(), vec![1,2,3]);
+
+        // already a multiple of the alignment, and it works like any other buffer
+        let mut buf_exact = CircularBuffer::<u64>::new_aligned(8, 16);
+        assert_eq!(buf_exact.capacity(), 8);
+        assert_eq!(buf_exact.buffer.as_ptr() as usize % 16, 0);
+        buf_exact.write_many(&[1,2,3,4,5,6]).unwrap();
+        buf_exact.read_many(4).unwrap();
+        buf_exact.write_many(&[7,8,9,10,11,12]).unwrap();
+        assert_eq!(buf_exact.read_many(8).unwrap(), vec![5,6,7,8,9,10,11,12]);
+
+        // element types with a destructor are dropped properly
+        let value = Rc::new(1);
+        let mut shared = CircularBuffer::<Rc<u8>>::new_aligned(3, 128);
+        assert_eq!(shared.buffer.as_ptr() as usize % 128, 0);
+        shared.write_many(&[Rc::clone(&value), Rc::clone(&value)]).unwrap();
+        assert_eq!(Rc::strong_count(&value), 3);
+        drop(shared.read().unwrap());
+        assert_eq!(Rc::strong_count(&value), 2);
+        drop(shared);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        assert!(CircularBuffer::<u8>::try_new(0).is_err());
+        assert_eq!(CircularBuffer::<u8>::try_new(1).unwrap().capacity(), 1);
+
+        let mut buf = CircularBuffer::<u8>::new(0);
+        assert!(buf.is_empty());
+        assert!(buf.is_full());
+        assert!(buf.write(1).is_err());
+        assert!(buf.push_front(1).is_err());
+        assert!(buf.write_many(&[1]).is_err());
+        assert!(buf.write_many(&[]).is_ok());
+        assert_eq!(buf.write_overwrite(1), Some(1));
+        assert!(buf.read().is_err());
+        assert!(buf.pop_back().is_err());
+        assert!(buf.peek().is_err());
+        assert!(buf.peek_back().is_err());
+        assert_eq!(buf.iter().count(), 0);
+        assert_eq!(buf.size(), 0);
+        assert_eq!(buf.to_string(), "[]");
+        buf.clear();
+        buf.retain(|_| true);
+    }
+
+    #[test]
+    fn test_on_full() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let rejected = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&rejected);
+
+        let mut buf = CircularBuffer::<u8>::new(2);
+        buf.on_full(Box::new(move || { counter.fetch_add(1, Ordering::SeqCst); }));
+
+        buf.write(1).unwrap();
+        buf.write(2).unwrap();
+        assert_eq!(rejected.load(Ordering::SeqCst), 0);
+
+        assert!(buf.write(3).is_err());
+        assert!(buf.write(4).is_err());
+        assert_eq!(rejected.load(Ordering::SeqCst), 2);
+
+        buf.read().unwrap();
+        buf.write(5).unwrap();
+        assert_eq!(rejected.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send<S: Send>() {}
+        fn assert_sync<S: Sync>() {}
+
+        // neither the callback nor the overflow policy stop a buffer from being moved to or shared with other threads
+        assert_send::<CircularBuffer<u8>>();
+        assert_sync::<CircularBuffer<u8>>();
+    }
+
+    #[test]
+    fn test_with_capacity_and_default() {
+        let mut buf = CircularBuffer::<u8>::with_capacity(3);
+        assert_eq!(buf.capacity(), 3);
+        assert!(buf.is_empty());
+        buf.write_many(&[1,2,3]).unwrap();
+        assert!(buf.is_full());
+
+        #[derive(Default)]
+        struct State {
+            history: CircularBuffer<u8>
+        }
+        let mut state = State::default();
+        assert_eq!(state.history.capacity(), 0);
+        assert!(state.history.write(1).is_err());
+
+        state.history = CircularBuffer::with_capacity(2);
+        state.history.write(1).unwrap();
+        assert_eq!(state.history.size(), 1);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let mut buf = CircularBuffer::<Vec<u8>>::new(3);
+        buf.write(vec![0]).unwrap();
+        buf.read().unwrap();
+        buf.write_many(&[vec![1,2], vec![], vec![3,4,5]]).unwrap();
+
+        let flat = buf.flatten();
+        assert_eq!(flat.capacity(), 5);
+        assert!(flat.is_full());
+        assert_eq!(flat.peek_many(5).unwrap(), vec![1,2,3,4,5]);
+    }
+
+    #[test]
+    fn test_write_slice() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_slice(&[1,2,3]).unwrap();
+        assert_eq!(buf.index_next_free, 3);
+        buf.read_many(2).unwrap();
+
+        // wraps around the end of the internal buffer
+        buf.write_slice(&[4,5,6]).unwrap();
+        assert_eq!(buf.index_next_free, 1);
+        assert_eq!(buf.size(), 4);
+        assert_eq!(buf.peek_many(4).unwrap(), vec![3,4,5,6]);
+
+        // exactly fills the buffer
+        buf.write_slice(&[7]).unwrap();
+        assert!(buf.is_full());
+        assert_eq!(buf.index_next_free, 2);
+
+        assert!(buf.write_slice(&[8]).is_err());
+        buf.write_slice(&[]).unwrap();
+        assert_eq!(buf.read_many(5).unwrap(), vec![3,4,5,6,7]);
+
+        let mut empty = CircularBuffer::<u8>::new(0);
+        empty.write_slice(&[]).unwrap();
+        assert!(empty.write_slice(&[1]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "sync")]
+    fn test_subscribe() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write(0).unwrap();
+
+        // subscribers only see what is written after they subscribed
+        let mut fast = buf.subscribe();
+        let mut slow = buf.subscribe();
+        assert!(!fast.has_new());
+
+        for i in 1..=3 {
+            buf.write_overwrite(i);
+            assert!(fast.has_new());
+            assert_eq!(fast.pull(&buf), vec![i]);
+            assert!(!fast.has_new());
+        }
+
+        // the slow subscriber reads at a lower rate, the data isn't consumed by the fast one
+        assert_eq!(slow.pull(&buf), vec![1,2,3]);
+        assert_eq!(buf.size(), 4);
+
+        // the slow subscriber falls behind more than the capacity of the buffer
+        for i in 4..=9 {
+            buf.write_overwrite(i);
+            assert_eq!(fast.pull(&buf), vec![i]);
+        }
+        assert_eq!(slow.pull(&buf), vec![6,7,8,9]);
+        assert_eq!(slow.missed(), 2);
+        assert_eq!(fast.missed(), 0);
+
+        drop(buf);
+        assert!(!fast.has_new());
+    }
+
+    #[test]
+    #[cfg(feature = "sync")]
+    fn test_subscribe_front_and_back() {
+        // elements inserted at the front are never delivered
+        let mut buf = CircularBuffer::<u8>::new(4);
+        let mut sub = buf.subscribe();
+        buf.push_front(1).unwrap();
+        assert!(!sub.has_new());
+        assert_eq!(sub.pull(&buf), vec![]);
+        buf.write(2).unwrap();
+        buf.push_front(0).unwrap();
+        assert_eq!(sub.pull(&buf), vec![2]);
+        assert_eq!(sub.missed(), 0);
+
+        // an element written after pop_back() is delivered, even though it takes the place of one that was pulled
+        let mut buf = CircularBuffer::<u8>::new(4);
+        let mut sub = buf.subscribe();
+        buf.write_many(&[1,2]).unwrap();
+        assert_eq!(sub.pull(&buf), vec![1,2]);
+        assert_eq!(buf.pop_back().unwrap(), 2);
+        assert!(!sub.has_new());
+        buf.write(3).unwrap();
+        assert!(sub.has_new());
+        assert_eq!(sub.pull(&buf), vec![3]);
+    }
+
+    #[test]
+    fn test_read_slice() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[1,2,3,4]).unwrap();
+        assert_eq!(buf.read_slice(3).unwrap(), vec![1,2,3]);
+        assert_eq!(buf.index_start, 3);
+
+        // the elements wrap around the end of the internal buffer
+        buf.write_many(&[5,6,7]).unwrap();
+        assert!(buf.read_slice(5).is_err());
+        assert_eq!(buf.read_slice(3).unwrap(), vec![4,5,6]);
+        assert_eq!(buf.index_start, 1);
+        assert_eq!(buf.size(), 1);
+
+        assert_eq!(buf.read_slice(0).unwrap(), vec![]);
+        assert_eq!(buf.read_slice(1).unwrap(), vec![7]);
+        assert!(buf.is_empty());
+        assert_eq!(buf.index_start, buf.index_next_free);
+    }
+
+    #[test]
+    fn test_trapezoidal_integral() {
+        let mut buf = CircularBuffer::<f32>::new(4);
+        assert_eq!(buf.trapezoidal_integral(1.0), None);
+        buf.write(1.0).unwrap();
+        assert_eq!(buf.trapezoidal_integral(1.0), None);
+
+        buf.write_many(&[1.0,1.0]).unwrap();
+        buf.read_many(3).unwrap();
+
+        // the samples wrap around the end of the internal buffer
+        // (0 + 2) / 2 * 0.5 + (2 + 4) / 2 * 0.5 + (4 + 2) / 2 * 0.5 = 0.5 + 1.5 + 1.5
+        buf.write_many(&[0.0,2.0,4.0,2.0]).unwrap();
+        assert_eq!(buf.trapezoidal_integral(0.5), Some(3.5));
+    }
+
+    #[test]
+    fn test_rotate() {
+        // full buffer, only the indices change
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[1,2,3,4,5]).unwrap();
+        buf.rotate_left(2);
+        assert_eq!(buf.index_start, 2);
+        assert_eq!(buf.peek_many(5).unwrap(), vec![3,4,5,1,2]);
+        buf.rotate_right(3);
+        assert_eq!(buf.peek_many(5).unwrap(), vec![5,1,2,3,4]);
+        buf.rotate_left(10);
+        assert_eq!(buf.peek_many(5).unwrap(), vec![5,1,2,3,4]);
+
+        // partially filled buffer that wraps around, only the elements rotate
+        let mut buf = CircularBuffer::<u8>::new(6);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(4).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
+        buf.rotate_left(1);
+        assert_eq!(buf.peek_many(4).unwrap(), vec![2,3,4,1]);
+        buf.rotate_left(3);
+        assert_eq!(buf.peek_many(4).unwrap(), vec![1,2,3,4]);
+        buf.rotate_right(5);
+        assert_eq!(buf.peek_many(4).unwrap(), vec![4,1,2,3]);
+        assert_eq!(buf.size(), 4);
+
+        // the buffer is still consistent afterwards
+        buf.write_many(&[5,6]).unwrap();
+        assert_eq!(buf.read_many(6).unwrap(), vec![4,1,2,3,5,6]);
+
+        let mut empty = CircularBuffer::<u8>::new(3);
+        empty.rotate_left(1);
+        empty.rotate_right(1);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_resize_keep_newest() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4,5]).unwrap();
+
+        // shrinking a wrapped buffer keeps the newest elements
+        buf.resize_keep_newest(3);
+        assert_eq!(buf.capacity(), 3);
+        assert!(buf.is_full());
+        assert_eq!(buf.index_start, 0);
+        assert_eq!(buf.index_next_free, 0);
+        assert_eq!(buf.peek_many(3).unwrap(), vec![3,4,5]);
+
+        // growing keeps everything
+        buf.resize_keep_newest(6);
+        assert_eq!(buf.capacity(), 6);
+        assert_eq!(buf.index_next_free, 3);
+        buf.write_many(&[6,7,8]).unwrap();
+        assert_eq!(buf.read_many(6).unwrap(), vec![3,4,5,6,7,8]);
+
+        // the alignment of an aligned buffer is preserved
+        let mut aligned = CircularBuffer::<u8>::new_aligned(16, 64);
+        aligned.resize_keep_newest(100);
+        assert_eq!(aligned.buffer.as_ptr() as usize % 64, 0);
+
+        aligned.resize_keep_newest(0);
+        assert_eq!(aligned.capacity(), 0);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut buf = CircularBuffer::<String>::new(4);
+        for value in ["a", "b", "c", "d", "e", "f"] {
+            buf.write_overwrite(String::from(value));
+        }
+
+        // the buffer wraps around, the oldest elements are dropped
+        buf.truncate(2);
+        assert_eq!(buf.size(), 2);
+        assert_eq!(buf.peek_many(2).unwrap(), vec!["e", "f"]);
+        assert!(buf.buffer.iter().filter(|value| !value.is_empty()).count() == 2);
+
+        // no-op when there aren't more elements than len
+        buf.truncate(3);
+        assert_eq!(buf.size(), 2);
+        buf.truncate(0);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_fill() {
+        let mut buf = CircularBuffer::<String>::new(4);
+        buf.write_many(&[String::from("a"), String::from("b"), String::from("c")]).unwrap();
+        buf.read().unwrap();
+
+        buf.fill(String::from("x"));
+        assert!(buf.is_full());
+        assert_eq!(buf.index_start, 0);
+        assert_eq!(buf.index_next_free, 0);
+        assert_eq!(buf.peek_many(4).unwrap(), vec!["x"; 4]);
+
+        let mut empty = CircularBuffer::<u8>::new(0);
+        empty.fill(1);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_append() {
+        let mut sink = CircularBuffer::<u8>::new(5);
+        sink.write_many(&[0,0,0,1]).unwrap();
+        sink.read_many(3).unwrap();
+
+        let mut producer = CircularBuffer::<u8>::new(3);
+        producer.write_many(&[2,3,4]).unwrap();
+        sink.append(&mut producer).unwrap();
+        assert!(producer.is_empty());
+        assert_eq!(sink.peek_many(4).unwrap(), vec![1,2,3,4]);
+
+        // not enough space, nothing is moved
+        producer.write_many(&[5,6]).unwrap();
+        assert!(sink.append(&mut producer).is_err());
+        assert_eq!(producer.size(), 2);
+        assert_eq!(sink.size(), 4);
+    }
+
+    #[test]
+    fn test_position_and_find() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        assert_eq!(buf.position(|_| true), None);
+
+        buf.write_many(&[9,9,9]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
+
+        // the stale slots contain 9, which must not be found
+        assert_eq!(buf.position(|value| *value > 2), Some(2));
+        assert_eq!(buf.find(|value| *value > 2), Some(&3));
+        assert_eq!(buf.position(|value| *value == 9), None);
+        assert_eq!(buf.find(|value| *value == 9), None);
+    }
+
+    #[test]
+    fn test_windows() {
+        use std::borrow::Cow;
+
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
+
+        let windows: Vec<Vec<u8>> = buf.windows(3).map(|window| window.to_vec()).collect();
+        assert_eq!(windows, vec![vec![1,2,3], vec![2,3,4]]);
+        assert_eq!(buf.windows(3).len(), 2);
+
+        // [1,2] is contiguous, [2,3] straddles the end of the internal buffer, [3,4] is contiguous again
+        let windows: Vec<Cow<[u8]>> = buf.windows(2).collect();
+        assert!(matches!(windows[0], Cow::Borrowed(_)));
+        assert!(matches!(windows[1], Cow::Owned(_)));
+        assert!(matches!(windows[2], Cow::Borrowed(_)));
+
+        assert_eq!(buf.windows(4).count(), 1);
+        assert_eq!(buf.windows(5).count(), 0);
+    }
+
+    #[test]
+    fn test_chunks() {
+        use std::borrow::Cow;
+
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(4).unwrap();
+        buf.write_many(&[1,2,3,4,5]).unwrap();
+
+        let chunks: Vec<Vec<u8>> = buf.chunks(2).map(|chunk| chunk.to_vec()).collect();
+        assert_eq!(chunks, vec![vec![1,2], vec![3,4], vec![5]]);
+        assert_eq!(buf.chunks(2).len(), 3);
+
+        // [1,2] straddles the end of the internal buffer
+        let chunks: Vec<Cow<[u8]>> = buf.chunks(2).collect();
+        assert!(matches!(chunks[0], Cow::Owned(_)));
+        assert!(matches!(chunks[1], Cow::Borrowed(_)));
+        assert!(matches!(chunks[2], Cow::Borrowed(_)));
+
+        assert_eq!(buf.chunks(5).count(), 1);
+        assert_eq!(buf.chunks(10).count(), 1);
+        buf.clear();
+        assert_eq!(buf.chunks(2).count(), 0);
+    }
+
+    #[test]
+    fn test_to_string_with() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2]).unwrap();
+        assert_eq!(buf.to_string_with("|", "_"), "[1|2|_|_]");
+        assert_eq!(buf.to_string_with(" ", "."), "[1 2 . .]");
+        assert_eq!(buf.to_string_with(",", "_"), buf.to_string());
+    }
+
+    #[test]
+    fn test_to_string_wrapped() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2,3,4]).unwrap();
+        assert_eq!(buf.to_string(), "[1,2,3,4]");
+        buf.read_many(2).unwrap();
+        assert_eq!(buf.to_string(), "[_,_,3,4]");
+        buf.write(5).unwrap();
+        assert_eq!(buf.to_string(), "[5,_,3,4]");
+        buf.write(6).unwrap();
+        assert_eq!(buf.to_string(), "[5,6,3,4]");
+        buf.read_many(3).unwrap();
+        assert_eq!(buf.to_string(), "[_,6,_,_]");
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2]).unwrap();
+
+        let snapshot = buf.snapshot();
+        buf.write_many(&[3,4,5]).unwrap();
+        assert_eq!(buf.peek_many(5).unwrap(), vec![1,2,3,4,5]);
+
+        buf.restore(snapshot);
+        assert_eq!(buf.size(), 2);
+        assert_eq!(buf.index_start, 3);
+        assert_eq!(buf.index_next_free, 0);
+        assert_eq!(buf.read_many(2).unwrap(), vec![1,2]);
+    }
+
+    #[test]
+    fn test_index() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
+
+        assert_eq!(buf[0], 1);
+        assert_eq!(buf[1], 2);
+        assert_eq!(buf[2], 3);
+        assert_eq!(buf.get(1), Some(&2));
+        assert_eq!(buf.get(3), None);
+
+        buf[1] = 5;
+        *buf.get_mut(2).unwrap() = 6;
+        assert_eq!(buf.get_mut(3), None);
+        assert_eq!(buf.read_many(3).unwrap(), vec![1,5,6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_index_out_of_bounds() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2]).unwrap();
+        let _ = buf[2];
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        fn hash_of(buf: &CircularBuffer<u8>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            buf.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let mut wrapped = CircularBuffer::<u8>::new(4);
+        wrapped.write_many(&[0,0,0]).unwrap();
+        wrapped.read_many(3).unwrap();
+        wrapped.write_many(&[1,2,3]).unwrap();
+
+        let mut unwrapped = CircularBuffer::<u8>::new(8);
+        unwrapped.write_many(&[1,2,3]).unwrap();
+
+        assert!(wrapped == unwrapped);
+        assert_eq!(hash_of(&wrapped), hash_of(&unwrapped));
+
+        unwrapped.write(4).unwrap();
+        assert!(wrapped != unwrapped);
+        assert_ne!(hash_of(&wrapped), hash_of(&unwrapped));
+    }
+
+    #[test]
+    fn test_extend_from_slice_overwrite() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2]).unwrap();
+
+        buf.extend_from_slice_overwrite(&[3,4]);
+        assert_eq!(buf.peek_many(4).unwrap(), vec![1,2,3,4]);
+        buf.extend_from_slice_overwrite(&[5]);
+        assert_eq!(buf.peek_many(4).unwrap(), vec![2,3,4,5]);
+        buf.extend_from_slice_overwrite(&[6,7,8,9,10,11]);
+        assert_eq!(buf.read_many(4).unwrap(), vec![8,9,10,11]);
+
+        buf.extend_from_slice_overwrite(&[1]);
+        assert_eq!(buf.read_many(1).unwrap(), vec![1]);
+
+        let mut empty = CircularBuffer::<u8>::new(0);
+        empty.extend_from_slice_overwrite(&[1,2]);
+        assert_eq!(empty.size(), 0);
+    }
+
+    #[test]
+    fn test_secure_clear() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write_many(&[4,5]).unwrap();
+
+        buf.secure_clear();
+        assert_eq!(buf.size(), 0);
+        assert_eq!(buf.index_start, 0);
+        assert_eq!(buf.index_next_free, 0);
+        // every slot is scrubbed, including the ones that were already read
+        assert!(buf.buffer.iter().all(|&slot| slot == 0));
+
+        buf.write(6).unwrap();
+        assert_eq!(buf.read().unwrap(), 6);
+    }
+
+    #[test]
+    fn test_drain_n() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4,5]).unwrap();
+
+        assert!(buf.drain_n(6).is_err());
+
+        let mut drain = buf.drain_n(4).unwrap();
+        assert_eq!(drain.len(), 4);
+        assert_eq!(drain.next(), Some(1));
+        assert_eq!(drain.next_back(), Some(4));
+        assert_eq!(drain.len(), 2);
+        // the remaining two elements are removed when the iterator is dropped early
+        drop(drain);
+        assert_eq!(buf.size(), 1);
+        assert_eq!(buf.index_start, 2);
+
+        buf.write_many(&[6,7]).unwrap();
+        assert_eq!(buf.drain_n(3).unwrap().rev().collect::<Vec<u8>>(), vec![7,6,5]);
+        assert!(buf.is_empty());
+        assert_eq!(buf.drain_n(0).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_growable() {
+        let mut buf = CircularBuffer::<u8>::new_growable(2, Some(6));
+        buf.write_many(&[1,2]).unwrap();
+        buf.read().unwrap();
+        buf.write(3).unwrap();
+        assert_eq!(buf.capacity(), 2);
+
+        // wrapped and full, so the next write doubles the capacity
+        buf.write(4).unwrap();
+        assert_eq!(buf.capacity(), 4);
+        assert_eq!(buf.peek_many(3).unwrap(), vec![2,3,4]);
+
+        // growing stops at the max capacity
+        buf.write_many(&[5,6,7]).unwrap();
+        assert_eq!(buf.capacity(), 6);
+        assert_eq!(buf.write(8), Err("CircularBuffer is full"));
+        assert_eq!(buf.read_many(6).unwrap(), vec![2,3,4,5,6,7]);
+
+        let mut unlimited = CircularBuffer::<u8>::new_growable(0, None);
+        unlimited.write_many(&[1,2,3,4,5]).unwrap();
+        assert_eq!(unlimited.capacity(), 8);
+
+        // the other ways of adding elements grow too
+        unlimited.write_slice(&[6,7,8,9]).unwrap();
+        assert_eq!(unlimited.capacity(), 16);
+        let mut other = CircularBuffer::from(vec![10; 8]);
+        unlimited.append(&mut other).unwrap();
+        assert_eq!(unlimited.capacity(), 32);
+        assert!(other.is_empty());
+        let mut front = CircularBuffer::<u8>::new_growable(1, None);
+        front.push_front(2).unwrap();
+        front.push_front(1).unwrap();
+        assert_eq!(front.capacity(), 2);
+        assert_eq!(front, vec![1,2]);
+
+        // regular buffers are not affected
+        let mut fixed = CircularBuffer::<u8>::new(1);
+        fixed.write(1).unwrap();
+        assert!(fixed.write(2).is_err());
+    }
+
+    #[test]
+    fn test_first_last() {
+        let mut buf = CircularBuffer::<u8>::new(3);
+        assert_eq!(buf.first(), None);
+        assert_eq!(buf.last(), None);
+
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write_many(&[4,5]).unwrap();
+        assert_eq!(buf.first(), Some(3));
+        assert_eq!(buf.last(), Some(5));
+        assert_eq!(buf.size(), 3);
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4,5]).unwrap();
+
+        buf.swap(0, 3);
+        assert_eq!(buf.peek_many(5).unwrap(), vec![4,2,3,1,5]);
+
+        assert_eq!(buf.swap_remove(1), Some(2));
+        assert_eq!(buf.peek_many(4).unwrap(), vec![4,5,3,1]);
+        assert_eq!(buf.swap_remove(3), Some(1));
+        assert_eq!(buf.swap_remove(3), None);
+        assert_eq!(buf.read_many(3).unwrap(), vec![4,5,3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_swap_out_of_bounds() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2]).unwrap();
+        buf.swap(0, 2);
+    }
+
+    #[test]
+    fn test_count_any_all() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
+
+        assert_eq!(buf.count(|&x| x % 2 == 0), 2);
+        assert!(buf.any(|&x| x == 4));
+        assert!(!buf.any(|&x| x == 0));
+        assert!(buf.all(|&x| x > 0));
+        assert!(!buf.all(|&x| x < 4));
+
+        // both stop at the first element that decides the result
+        let mut calls = 0;
+        assert!(buf.any(|&x| { calls += 1; x == 2 }));
+        assert_eq!(calls, 2);
+        calls = 0;
+        assert!(!buf.all(|&x| { calls += 1; x == 9 }));
+        assert_eq!(calls, 1);
+
+        buf.clear();
+        assert_eq!(buf.count(|_| true), 0);
+        assert!(!buf.any(|_| true));
+        assert!(buf.all(|_| false));
+    }
+
+    #[test]
+    fn test_eq_slice() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
+
+        assert_eq!(buf, [1,2,3][..]);
+        assert_eq!(buf, &[1,2,3][..]);
+        assert_eq!(buf, vec![1,2,3]);
+        assert_ne!(buf, [1,2][..]);
+        assert_ne!(buf, vec![1,2,3,4]);
+        assert_ne!(buf, vec![1,2,4]);
+        assert_eq!(format!("{:?}", buf), "[1, 2, 3]");
+
+        buf.clear();
+        assert_eq!(buf, Vec::new());
+    }
+
+    #[test]
+    fn test_back_nth() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
+
+        assert_eq!(buf.back_nth(0), Some(&3));
+        assert_eq!(buf.back_nth(0), buf.peek_back().ok());
+        assert_eq!(buf.back_nth(1), Some(&2));
+        assert_eq!(buf.back_nth(2), Some(&1));
+        assert_eq!(buf.back_nth(3), None);
+    }
+
+    #[test]
+    fn test_map_into() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
+
+        let mapped: CircularBuffer<f64> = buf.map_into(|&x| f64::from(x) * 0.5);
+        assert_eq!(mapped.capacity(), 4);
+        assert_eq!(mapped, vec![0.5, 1.0, 1.5]);
+        assert_eq!(buf, vec![1,2,3]);
+    }
+
+    #[test]
+    fn test_len() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        assert_eq!(buf.len(), 0);
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read().unwrap();
+        assert_eq!(buf.len(), 2);
+        assert_eq!(buf.len(), buf.size());
+    }
+
+    #[test]
+    fn test_write_many_all_or_nothing() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write_many(&[1,2]).unwrap();
+
+        let (index_start, index_next_free) = (buf.index_start, buf.index_next_free);
+        assert_eq!(buf.write_many(&[3,4]), Err("CircularBuffer does not have enough space for the provided elements"));
+        assert_eq!(buf.size(), 3);
+        assert_eq!(buf.index_start, index_start);
+        assert_eq!(buf.index_next_free, index_next_free);
+        assert_eq!(buf, vec![0,1,2]);
+
+        buf.write_many(&[3]).unwrap();
+        assert_eq!(buf, vec![0,1,2,3]);
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut buf = CircularBuffer::<u8>::new(8);
+        buf.write_many(&[0,0,0,0,0,0]).unwrap();
+        buf.read_many(6).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
+
+        buf.shrink_to_fit();
+        assert_eq!(buf.capacity(), 3);
+        assert_eq!(buf.index_start, 0);
+        assert!(buf.is_full());
+        assert_eq!(buf, vec![1,2,3]);
+        assert!(buf.write(4).is_err());
+
+        buf.clear();
+        buf.shrink_to_fit();
+        assert_eq!(buf.capacity(), 0);
+    }
+
+    #[test]
+    fn test_write_iter() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+
+        assert_eq!(buf.write_iter((1..3).map(|x| x * 10)), 2);
+        assert_eq!(buf, vec![10,20]);
+
+        // stops when the buffer is full, without losing the next element
+        let mut source = 3..10;
+        assert_eq!(buf.write_iter(&mut source), 2);
+        assert_eq!(buf, vec![10,20,3,4]);
+        assert_eq!(source.next(), Some(5));
+        assert_eq!(buf.write_iter(source), 0);
+
+        let mut growable = CircularBuffer::<u8>::new_growable(1, None);
+        assert_eq!(growable.write_iter(0..5), 5);
+        assert_eq!(growable.capacity(), 8);
+    }
+
+    #[test]
+    fn test_read_replace() {
+        let mut buf = CircularBuffer::<Box<[u8]>>::new(2);
+        buf.write(vec![1,2].into_boxed_slice()).unwrap();
+        buf.write(vec![3].into_boxed_slice()).unwrap();
+        buf.read().unwrap();
+
+        let placeholder: Box<[u8]> = vec![9].into_boxed_slice();
+        assert_eq!(&*buf.read_replace(placeholder).unwrap(), &[3]);
+        assert!(buf.is_empty());
+        assert_eq!(&*buf.buffer[1], &[9]);
+        assert_eq!(buf.read_replace(Box::new([])), Err("CircularBuffer is empty"));
+    }
+
+    #[test]
+    fn test_as_slices() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[1,2,3]).unwrap();
+        assert_eq!(buf.as_slices(), (&[1,2,3][..], &[][..]));
+
+        buf.read_many(2).unwrap();
+        buf.write_many(&[4,5,6]).unwrap();
+        assert_eq!(buf.as_slices(), (&[3,4,5][..], &[6][..]));
+
+        let (first, second) = buf.as_mut_slices();
+        for element in first.iter_mut().chain(second.iter_mut()) {
+            *element *= 10;
+        }
+        assert_eq!(buf, vec![30,40,50,60]);
+
+        buf.clear();
+        assert_eq!(buf.as_mut_slices(), (&mut [][..], &mut [][..]));
+    }
+
+    #[test]
+    fn test_try_with_capacity() {
+        let mut buf = CircularBuffer::<u8>::try_with_capacity(4).unwrap();
+        assert_eq!(buf.capacity(), 4);
+        buf.write(1).unwrap();
+        assert_eq!(buf.read().unwrap(), 1);
+
+        assert_eq!(CircularBuffer::<u8>::try_with_capacity(usize::MAX).err(), Some("CircularBuffer capacity overflow"));
+        assert_eq!(CircularBuffer::<u64>::try_with_capacity(usize::MAX / 4).err(), Some("CircularBuffer capacity overflow"));
+        assert_eq!(CircularBuffer::<u8>::try_with_capacity(0).unwrap().capacity(), 0);
+    }
+
+    #[test]
+    fn test_split_at() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4,5]).unwrap();
+
+        for n in 0..=5 {
+            let (older, newer) = buf.split_at(n);
+            assert_eq!(older.len(), n);
+            assert_eq!(older.copied().collect::<Vec<u8>>(), (1..=n as u8).collect::<Vec<u8>>());
+            assert_eq!(newer.copied().collect::<Vec<u8>>(), (n as u8 + 1..=5).collect::<Vec<u8>>());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_split_at_out_of_bounds() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2]).unwrap();
+        buf.split_at(3);
+    }
+
+    #[test]
+    fn test_reset() {
+        use std::rc::Rc;
+
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write_many(&[4,5]).unwrap();
+        buf.reset();
+        assert!(buf.is_empty());
+        assert_eq!(buf.index_start, 0);
+        assert_eq!(buf.index_next_free, 0);
+        buf.write(6).unwrap();
+        assert_eq!(buf, vec![6]);
+
+        // elements that need to be dropped are dropped right away
+        let shared = Rc::new(0);
+        let mut rcs = CircularBuffer::<Rc<u8>>::new(3);
+        rcs.write(Rc::clone(&shared)).unwrap();
+        rcs.write(Rc::clone(&shared)).unwrap();
+        assert_eq!(Rc::strong_count(&shared), 3);
+        rcs.reset();
+        assert_eq!(Rc::strong_count(&shared), 1);
+        assert!(rcs.is_empty());
+    }
+
+    #[test]
+    fn test_layout() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        assert_eq!(buf.layout(), (0, 0, 0));
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write_many(&[4,5]).unwrap();
+        assert_eq!(buf.layout(), (2, 1, 3));
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut buf = CircularBuffer::<u8>::new(8);
+        buf.write_many(&[0,0,0,0,0,0]).unwrap();
+        buf.read_many(6).unwrap();
+        // the run of 2s crosses the end of the internal buffer
+        buf.write_many(&[1,1,2,2,2,3,1,1]).unwrap();
+
+        buf.dedup();
+        assert_eq!(buf, vec![1,2,3,1]);
+        assert_eq!(buf.physical_index(buf.size()), buf.index_next_free);
+        buf.write_many(&[1,4]).unwrap();
+        buf.dedup();
+        assert_eq!(buf, vec![1,2,3,1,4]);
+
+        buf.dedup_by_key(|&x| x % 2);
+        assert_eq!(buf, vec![1,2,3,4]);
+
+        buf.clear();
+        buf.dedup();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_ord() {
+        let mut wrapped = CircularBuffer::<u8>::new(4);
+        wrapped.write_many(&[0,0,0]).unwrap();
+        wrapped.read_many(3).unwrap();
+        wrapped.write_many(&[1,2,3]).unwrap();
+
+        let empty = CircularBuffer::<u8>::new(8);
+        let prefix = CircularBuffer::from(vec![1,2]);
+        let larger = CircularBuffer::from(vec![1,3]);
+        let same = CircularBuffer::from(vec![1,2,3]);
+
+        assert!(empty < prefix);
+        assert!(prefix < wrapped);
+        assert!(wrapped < larger);
+        assert_eq!(wrapped.cmp(&same), std::cmp::Ordering::Equal);
+        assert_eq!(CircularBuffer::<f64>::from(vec![f64::NAN]).partial_cmp(&CircularBuffer::from(vec![1.0])), None);
+
+        let mut buffers = [larger, wrapped, empty, prefix, same];
+        buffers.sort();
+        let sorted: Vec<Vec<u8>> = buffers.iter().map(|buf| buf.iter().copied().collect()).collect();
+        assert_eq!(sorted, vec![vec![], vec![1,2], vec![1,2,3], vec![1,2,3], vec![1,3]]);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
+
+        assert_eq!(buf.set(0, 10), Ok(1));
+        assert_eq!(buf.set(2, 30), Ok(3));
+        assert_eq!(buf.set(3, 40), Err("CircularBuffer index out of bounds"));
+        assert_eq!(buf.layout(), (3, 2, 3));
+        assert_eq!(buf, vec![10,2,30]);
+    }
+
+    #[test]
+    fn test_iter_rev() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
+
+        assert_eq!(buf.iter_rev().copied().collect::<Vec<u8>>(), vec![3,2,1]);
+        assert_eq!(buf.iter_rev().len(), 3);
+        buf.clear();
+        assert_eq!(buf.iter_rev().next(), None);
+    }
+
+    #[test]
+    fn test_try_from_slice() {
+        let mut buf = CircularBuffer::try_from_slice(4, &[1u8,2]).unwrap();
+        assert_eq!(buf.capacity(), 4);
+        assert_eq!(buf, vec![1,2]);
+        buf.write_many(&[3,4]).unwrap();
+        assert!(buf.is_full());
+
+        assert_eq!(CircularBuffer::try_from_slice(2, &[1u8,2]).unwrap(), vec![1,2]);
+        assert_eq!(CircularBuffer::try_from_slice(1, &[1u8,2]).err(), Some("CircularBuffer does not have enough space for the provided elements"));
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut buf = CircularBuffer::<Vec<u8>>::new_aligned(4, 64);
+        let capacity = buf.capacity();
+        buf.write_many(&vec![vec![0]; capacity - 1]).unwrap();
+        buf.read_many(capacity - 1).unwrap();
+        buf.write_many(&[vec![1], vec![2], vec![3]]).unwrap();
+
+        let clone = buf.clone();
+        assert_eq!(clone, buf);
+        assert_eq!(clone.layout(), buf.layout());
+        assert_eq!(clone.buffer.alignment(), 64);
+
+        // same capacity, so the internal buffer is reused
+        let mut target = CircularBuffer::<Vec<u8>>::new_aligned(4, 64);
+        target.write(vec![9]).unwrap();
+        let ptr = target.buffer.as_ptr();
+        target.clone_from(&buf);
+        assert_eq!(target.buffer.as_ptr(), ptr);
+        assert_eq!(target, buf);
+        assert_eq!(target.layout(), buf.layout());
+
+        // different capacity, so it falls back to clone()
+        let mut other = CircularBuffer::<Vec<u8>>::new(2);
+        other.clone_from(&buf);
+        assert_eq!(other.capacity(), capacity);
+        assert_eq!(other, buf);
+    }
+
+    #[test]
+    fn test_write_many_panicking_clone() {
+        use std::cell::Cell;
+        use std::panic::{self, AssertUnwindSafe};
+
+        thread_local!(static CLONES: Cell<usize> = const { Cell::new(0) });
+
+        // panics on the 3rd call to clone()
+        #[derive(Default, Debug, PartialEq)]
+        struct Fragile(u8);
+
+        impl Clone for Fragile {
+            fn clone(&self) -> Self {
+                CLONES.with(|clones| clones.set(clones.get() + 1));
+                if CLONES.with(Cell::get) == 3 {
+                    panic!("clone failed");
+                }
+                Fragile(self.0)
+            }
+        }
+
+        let mut buf = CircularBuffer::<Fragile>::new(5);
+        buf.write(Fragile(0)).unwrap();
+        buf.write(Fragile(0)).unwrap();
+        buf.read_many(2).unwrap();
+        CLONES.with(|clones| clones.set(0));
+
+        let values = [Fragile(1), Fragile(2), Fragile(3), Fragile(4)];
+        let result = panic::catch_unwind(AssertUnwindSafe(|| buf.write_many(&values)));
+        assert!(result.is_err());
+
+        assert_eq!(buf.size(), 2);
+        assert_eq!(buf.iter().collect::<Vec<&Fragile>>(), vec![&Fragile(1), &Fragile(2)]);
+        buf.write(Fragile(5)).unwrap();
+        assert_eq!(buf.read().unwrap(), Fragile(1));
+        assert_eq!(buf.read().unwrap(), Fragile(2));
+        assert_eq!(buf.read().unwrap(), Fragile(5));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_extend_overwrite() {
+        use std::rc::Rc;
+
+        let mut buf = CircularBuffer::<u32>::new(3);
+        buf.extend_overwrite(1..=2);
+        assert_eq!(buf, vec![1,2]);
+        buf.extend_overwrite(3..=7);
+        assert_eq!(buf, vec![5,6,7]);
+        buf.extend_overwrite(None);
+        assert_eq!(buf, vec![5,6,7]);
+
+        // the overwritten elements are dropped
+        let value = Rc::new(0);
+        let mut buf = CircularBuffer::<Rc<u32>>::new(2);
+        buf.extend_overwrite(vec![Rc::clone(&value); 5]);
+        assert_eq!(Rc::strong_count(&value), 3);
+        buf.clear();
+        assert_eq!(Rc::strong_count(&value), 1);
+
+        let mut empty = CircularBuffer::<u32>::new(0);
+        empty.extend_overwrite(1..=3);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_occupancy() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        assert_eq!(buf.occupancy(), 0.0);
+        buf.write_many(&[1,2,3]).unwrap();
+        assert_eq!(buf.occupancy(), 0.75);
+        buf.write(4).unwrap();
+        assert_eq!(buf.occupancy(), 1.0);
+
+        assert_eq!(CircularBuffer::<u8>::new(0).occupancy(), 0.0);
+    }
+
+    #[test]
+    fn test_flush_to() {
+        use std::io;
+
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
+
+        let mut output = Vec::new();
+        assert_eq!(buf.flush_to(&mut output).unwrap(), 4);
+        assert_eq!(output, vec![1,2,3,4]);
+        assert!(buf.is_empty());
+        assert_eq!(buf.flush_to(&mut output).unwrap(), 0);
+
+        // accepts 3 bytes, then fails
+        struct Failing(usize);
+
+        impl io::Write for Failing {
+            fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+                if self.0 == 0 {
+                    return Err(io::Error::other("disconnected"));
+                }
+                let written = data.len().min(self.0);
+                self.0 -= written;
+                Ok(written)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        buf.write_many(&[1,2,3,4]).unwrap();
+        assert!(buf.flush_to(&mut Failing(3)).is_err());
+        assert_eq!(buf, vec![4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "writer accepted more bytes")]
+    fn test_flush_to_overreporting_writer() {
+        use std::io;
+
+        // claims to have written more than it was given
+        struct Lying;
+
+        impl io::Write for Lying {
+            fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+                Ok(data.len() + 1)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2]).unwrap();
+        let _ = buf.flush_to(&mut Lying);
+    }
+
+    #[test]
+    fn test_fill_from() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+
+        // the free space wraps around, a slice reader fills both runs in one call
+        let mut input: &[u8] = &[1,2,3,4,5,6];
+        assert_eq!(buf.fill_from(&mut input).unwrap(), 4);
+        assert_eq!(buf, vec![0,1,2,3,4]);
+        assert_eq!(input, &[5,6]);
+
+        // full
+        assert_eq!(buf.fill_from(&mut input).unwrap(), 0);
+
+        buf.read_many(2).unwrap();
+        assert_eq!(buf.fill_from(&mut input).unwrap(), 2);
+        assert_eq!(buf, vec![2,3,4,5,6]);
+
+        // end of the reader
+        buf.read_many(5).unwrap();
+        assert_eq!(buf.fill_from(&mut input).unwrap(), 0);
+        assert!(buf.is_empty());
+
+        assert_eq!(CircularBuffer::<u8>::new(0).fill_from(&mut &[1u8][..]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_saturating_write() {
+        let mut buf = CircularBuffer::<u8>::new(2);
+        buf.saturating_write(1);
+        buf.saturating_write(2);
+        buf.saturating_write(3);
+        buf.saturating_write(4);
+        assert_eq!(buf, vec![1,2]);
+        assert_eq!(buf.dropped_count(), 2);
+
+        // clearing the buffer doesn't reset the counter
+        buf.clear();
+        buf.saturating_write(5);
+        assert_eq!(buf, vec![5]);
+        assert_eq!(buf.dropped_count(), 2);
+        buf.reset_dropped_count();
+        assert_eq!(buf.dropped_count(), 0);
+
+        let mut empty = CircularBuffer::<u8>::new(0);
+        empty.saturating_write(1);
+        assert_eq!(empty.dropped_count(), 1);
+    }
+
+    #[test]
+    fn test_binary_search() {
+        let mut buf = CircularBuffer::<u32>::new(6);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(4).unwrap();
+        buf.write_many(&[10,20,30,40,50]).unwrap();
+
+        assert_eq!(buf.binary_search(&10), Ok(0));
+        assert_eq!(buf.binary_search(&30), Ok(2));
+        assert_eq!(buf.binary_search(&50), Ok(4));
+        assert_eq!(buf.binary_search(&5), Err(0));
+        assert_eq!(buf.binary_search(&35), Err(3));
+        assert_eq!(buf.binary_search(&60), Err(5));
+
+        assert_eq!(CircularBuffer::<u32>::new(0).binary_search(&1), Err(0));
+    }
+
+    #[test]
+    fn test_keep_newest_oldest() {
+        use std::rc::Rc;
+
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4,5]).unwrap();
+
+        buf.keep_newest(4);
+        assert_eq!(buf, vec![2,3,4,5]);
+        buf.keep_oldest(2);
+        assert_eq!(buf, vec![2,3]);
+        buf.keep_newest(3);
+        buf.keep_oldest(3);
+        assert_eq!(buf, vec![2,3]);
+
+        // the tail moved back across the wrap, so writing continues right after the kept elements
+        buf.write_many(&[6,7,8]).unwrap();
+        assert_eq!(buf, vec![2,3,6,7,8]);
+        assert!(buf.is_full());
+
+        let value = Rc::new(0);
+        let mut rcs = CircularBuffer::<Rc<u8>>::new(4);
+        rcs.write_many(&[Rc::clone(&value), Rc::clone(&value), Rc::clone(&value)]).unwrap();
+        rcs.keep_oldest(1);
+        assert_eq!(Rc::strong_count(&value), 2);
+        rcs.keep_newest(0);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_write_partial() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(2).unwrap();
+
+        let values = [1,2,3,4,5];
+        let written = buf.write_partial(&values);
+        assert_eq!(written, 3);
+        assert_eq!(buf, vec![0,1,2,3]);
+        assert_eq!(buf.write_partial(&values[written..]), 0);
+
+        buf.read_many(2).unwrap();
+        assert_eq!(buf.write_partial(&values[written..]), 2);
+        assert_eq!(buf, vec![2,3,4,5]);
+        assert_eq!(buf.write_partial(&[]), 0);
+
+        // a growable buffer grows instead
+        let mut growable = CircularBuffer::<u8>::new_growable(2, None);
+        assert_eq!(growable.write_partial(&values), 5);
+        assert_eq!(growable, vec![1,2,3,4,5]);
+    }
+
+    #[test]
+    fn test_eq_layout_independent() {
+        fn assert_eq_relation<T: Eq + std::fmt::Debug>(a: &T, b: &T) {
+            assert_eq!(a, b);
+            assert_eq!(b, a);
+        }
+
+        // the elements wrap around the end of the internal buffer, so the tail is before the head
+        let mut wrapped = CircularBuffer::<u8>::new(4);
+        wrapped.write_many(&[0,0,0]).unwrap();
+        wrapped.read_many(3).unwrap();
+        wrapped.write_many(&[1,2,3]).unwrap();
+        assert!(wrapped.index_next_free < wrapped.index_start);
+
+        let mut fresh = CircularBuffer::<u8>::new(4);
+        fresh.write_many(&[1,2,3]).unwrap();
+        assert_ne!(wrapped.layout(), fresh.layout());
+        assert_eq_relation(&wrapped, &fresh);
+
+        // the capacity doesn't matter either
+        let mut larger = CircularBuffer::<u8>::new(10);
+        larger.write_many(&[1,2,3]).unwrap();
+        assert_eq_relation(&wrapped, &larger);
+
+        // but the order and the amount of elements do
+        let mut reversed = CircularBuffer::<u8>::new(4);
+        reversed.write_many(&[3,2,1]).unwrap();
+        assert_ne!(wrapped, reversed);
+        fresh.read().unwrap();
+        assert_ne!(wrapped, fresh);
+        assert_eq!(CircularBuffer::<u8>::new(0), CircularBuffer::<u8>::new(3));
+    }
+
+    #[test]
+    fn test_overwrite_mode() {
+        let mut buf = CircularBuffer::<u8>::new_overwriting(3);
+        assert!(buf.is_overwriting());
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.write(4).unwrap();
+        assert_eq!(buf, vec![2,3,4]);
+        buf.write_many(&[5,6]).unwrap();
+        assert_eq!(buf, vec![4,5,6]);
+        buf.write_many(&[7,8,9,10]).unwrap();
+        assert_eq!(buf, vec![8,9,10]);
+        buf.write(11).unwrap();
+
+        // write_many_tracked() never evicts, so it still fails on a full buffer
+        assert!(buf.write_many_tracked(&[12]).is_err());
+        assert_eq!(buf, vec![9,10,11]);
+
+        // switched off again, a full buffer fails like usual
+        buf.set_overwrite(false);
+        assert!(buf.write(12).is_err());
+        assert!(buf.write_many(&[12]).is_err());
+        assert_eq!(buf, vec![9,10,11]);
+
+        // a buffer with a capacity of 0 can't hold anything, even in overwrite mode
+        let mut empty = CircularBuffer::<u8>::new_overwriting(0);
+        assert!(empty.write(1).is_err());
+        assert!(empty.write_many(&[1]).is_err());
+        assert!(empty.write_many(&[]).is_ok());
+
+        assert!(!CircularBuffer::<u8>::new(3).is_overwriting());
+    }
+
+    #[test]
+    fn test_indexed_iter() {
+        let mut buf = CircularBuffer::<char>::new(4);
+        buf.write_many(&['x','x','x']).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&['a','b','c']).unwrap();
+
+        let pairs: Vec<(usize, &char)> = buf.indexed_iter().collect();
+        assert_eq!(pairs, vec![(0, &'a'), (1, &'b'), (2, &'c')]);
+        for (index, value) in buf.indexed_iter() {
+            assert_eq!(buf[index], *value);
+        }
+        assert_eq!(buf.indexed_iter().find(|(_, value)| **value == 'c').map(|(index, _)| index), Some(2));
+    }
+
+    #[test]
+    fn test_clone_empty() {
+        let mut buf = CircularBuffer::<u8>::new_overwriting(3);
+        buf.write_many(&[1,2,3]).unwrap();
+
+        let mut empty = buf.clone_empty();
+        assert!(empty.is_empty());
+        assert_eq!(empty.capacity(), 3);
+        assert!(empty.is_overwriting());
+        empty.write_many(&[4,5,6,7]).unwrap();
+        assert_eq!(empty, vec![5,6,7]);
+        assert_eq!(buf, vec![1,2,3]);
+
+        let aligned = CircularBuffer::<u8>::new_aligned(4, 64);
+        assert_eq!(aligned.clone_empty().buffer.alignment(), 64);
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut buf = CircularBuffer::<String>::new(5);
+        buf.write_many(&[String::new(), String::new(), String::new()]).unwrap();
+        buf.read_many(3).unwrap();
+        for value in &["a", "b", "c", "d"] {
+            buf.write(value.to_string()).unwrap();
+        }
+
+        let tail = buf.split_off(1);
+        assert_eq!(buf, vec![String::from("a")]);
+        assert_eq!(tail, vec![String::from("b"), String::from("c"), String::from("d")]);
+        assert_eq!(tail.capacity(), 5);
+
+        // this buffer continues right after the kept elements
+        buf.write(String::from("e")).unwrap();
+        assert_eq!(buf, vec![String::from("a"), String::from("e")]);
+
+        assert!(buf.split_off(2).is_empty());
+        assert_eq!(buf.split_off(0).size(), 2);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "split index out of bounds")]
+    fn test_split_off_out_of_bounds() {
+        let mut buf = CircularBuffer::<u8>::new(3);
+        buf.write(1).unwrap();
+        buf.split_off(2);
+    }
+
+    #[test]
+    fn test_peek_chunk() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
+
+        assert_eq!(buf.peek_chunk(2), Some(&[1,2][..]));
+        assert_eq!(buf.peek_chunk(0), Some(&[][..]));
+        // the third element is stored at the start of the internal buffer
+        assert_eq!(buf.peek_chunk(3), None);
+        assert_eq!(buf.peek_chunk(5), None);
+        assert_eq!(buf.size(), 4);
+    }
+
+    #[test]
+    fn test_peek_cow() {
+        use std::borrow::Cow;
+
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
+
+        let contiguous = buf.peek_cow(2).unwrap();
+        assert!(matches!(contiguous, Cow::Borrowed(_)));
+        assert_eq!(&*contiguous, &[1,2]);
+
+        let wrapped = buf.peek_cow(4).unwrap();
+        assert!(matches!(wrapped, Cow::Owned(_)));
+        assert_eq!(&*wrapped, &[1,2,3,4]);
+
+        assert_eq!(buf.peek_cow(5), Err("CircularBuffer does not contain the amount of requested elements"));
+        assert_eq!(buf.size(), 4);
+    }
+
+    #[test]
+    fn test_drain_while() {
+        let mut buf = CircularBuffer::<u32>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,3,5,2,7]).unwrap();
+
+        assert_eq!(buf.drain_while(|value| value % 2 == 1), vec![1,3,5]);
+        assert_eq!(buf, vec![2,7]);
+        assert_eq!(buf.drain_while(|value| value % 2 == 1), Vec::<u32>::new());
+        assert_eq!(buf.drain_while(|_| true), vec![2,7]);
+        assert!(buf.is_empty());
+        assert_eq!(buf.drain_while(|_| true), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_grow_to() {
         let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
 
-        let write_result = buf.write(1);
-        assert!(write_result.is_ok());
-        assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.size(), 1);
+        buf.grow_to(2);
+        assert_eq!(buf.capacity(), 4);
+        assert_eq!(buf.layout(), (3, 3, 4));
 
-        assert_eq!(buf.index_start, 0);
-        assert_eq!(buf.index_next_free, 1);
+        buf.grow_to(6);
+        assert_eq!(buf.capacity(), 6);
+        assert_eq!(buf.layout(), (0, 4, 4));
+        assert_eq!(buf, vec![1,2,3,4]);
+        buf.write_many(&[5,6]).unwrap();
+        assert_eq!(buf, vec![1,2,3,4,5,6]);
+    }
 
-        assert_eq!(buf.buffer[0], 1);
+    #[test]
+    fn test_checked_write_many() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write(0).unwrap();
+        assert_eq!(buf.checked_write_many(&[1,2,3,4,5]), Err(2));
+        assert_eq!(buf, vec![0]);
+        assert_eq!(buf.checked_write_many(&[1,2,3]), Ok(()));
+        assert_eq!(buf.checked_write_many(&[4]), Err(1));
+        assert_eq!(buf.checked_write_many(&[]), Ok(()));
+        assert_eq!(buf, vec![0,1,2,3]);
 
+        // a growable buffer only fails once it has reached its max capacity
+        let mut growable = CircularBuffer::<u8>::new_growable(2, Some(4));
+        assert_eq!(growable.checked_write_many(&[1,2,3,4,5,6]), Err(2));
+        assert_eq!(growable.capacity(), 4);
+        assert!(growable.is_empty());
     }
 
     #[test]
-    fn test_basic_read() {
+    fn test_as_vec() {
         let mut buf = CircularBuffer::<u8>::new(4);
+        assert_eq!(buf.as_vec(), Vec::<u8>::new());
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
+        assert_eq!(buf.as_vec(), vec![1,2,3]);
+        assert_eq!(buf.as_vec(), buf.peek_many(buf.size()).unwrap());
+        assert_eq!(buf.size(), 3);
+    }
 
-        let write_result = buf.write(1);
-        assert!(write_result.is_ok());
-        assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.size(), 1);
+    #[test]
+    fn test_zero_length_bulk_operations() {
+        for &(capacity, size) in &[(0, 0), (3, 0), (3, 2), (3, 3)] {
+            let mut buf = CircularBuffer::<u8>::new(capacity);
+            buf.write_many(&vec![1; size]).unwrap();
+            let layout = buf.layout();
 
-        let read_result = buf.read();
-        assert!(read_result.is_ok());
-        assert!(buf.is_empty());
-        assert_eq!(buf.size(), 0);
-        assert_eq!(read_result.unwrap(), 1);
+            assert_eq!(buf.write_many(&[]), Ok(()));
+            assert_eq!(buf.write_slice(&[]), Ok(()));
+            assert_eq!(buf.write_many_tracked(&[]), Ok(size..size));
+            assert_eq!(buf.read_many(0), Ok(vec![]));
+            assert_eq!(buf.read_slice(0), Ok(vec![]));
+            assert_eq!(buf.peek_many(0), Ok(vec![]));
+            assert_eq!(buf.size(), size);
+            assert_eq!(buf.layout(), layout);
+        }
+    }
 
+    #[test]
+    fn test_rposition() {
+        let mut buf = CircularBuffer::<u32>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,4,3,6,5]).unwrap();
+
+        assert_eq!(buf.rposition(|value| value % 2 == 0), Some(3));
+        assert_eq!(buf.position(|value| value % 2 == 0), Some(1));
+        assert_eq!(buf.get(buf.rposition(|value| *value < 4).unwrap()), Some(&3));
+        assert_eq!(buf.rposition(|value| *value > 10), None);
+
+        // it stops at the newest match
+        let mut checked = Vec::new();
+        buf.rposition(|value| {
+            checked.push(*value);
+            *value == 6
+        });
+        assert_eq!(checked, vec![5,6]);
     }
 
     #[test]
-    fn test_basic_peek() {
+    fn test_high_water_mark() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        assert_eq!(buf.high_water_mark(), 0);
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write(4).unwrap();
+        assert_eq!(buf.high_water_mark(), 3);
+        buf.write_slice(&[5,6]).unwrap();
+        assert_eq!(buf.high_water_mark(), 4);
+        buf.push_front(0).unwrap();
+        assert_eq!(buf.high_water_mark(), 5);
+
+        buf.clear();
+        assert_eq!(buf.high_water_mark(), 5);
+        buf.write(1).unwrap();
+        buf.reset_high_water_mark();
+        assert_eq!(buf.high_water_mark(), 1);
+        buf.write_iter(vec![2,3]);
+        assert_eq!(buf.high_water_mark(), 3);
+
+        // elements that are already there when the buffer is created count too
+        assert_eq!(CircularBuffer::from(vec![1,2]).high_water_mark(), 2);
+    }
+
+    #[test]
+    fn test_swap_with_slice() {
         let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
 
-        let write_result = buf.write(1);
-        assert!(write_result.is_ok());
-        assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.size(), 1);
+        let mut work = [7,8,9];
+        buf.swap_with_slice(&mut work).unwrap();
+        assert_eq!(buf, vec![7,8,9]);
+        assert_eq!(work, [1,2,3]);
+        assert_eq!(buf.layout(), (3, 2, 3));
 
-        let peek_result = buf.peek();
-        assert!(peek_result.is_ok());
-        assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.size(), 1);
-        assert_eq!(*peek_result.unwrap(), 1);
+        let mut short = [0,0];
+        assert_eq!(buf.swap_with_slice(&mut short), Err("CircularBuffer size does not match the length of the slice"));
+        assert_eq!(buf, vec![7,8,9]);
+        assert_eq!(short, [0,0]);
+    }
 
-        let read_result = buf.read();
-        assert!(read_result.is_ok());
+    #[test]
+    fn test_move_into() {
+        use std::rc::Rc;
+
+        let value = Rc::new(0);
+        let mut buf = CircularBuffer::<Rc<u8>>::new(4);
+        buf.write_many(&[Rc::new(9), Rc::new(9), Rc::new(9)]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[Rc::clone(&value), Rc::clone(&value), Rc::new(1)]).unwrap();
+
+        let mut dst = CircularBuffer::<Rc<u8>>::new(3);
+        buf.move_into(&mut dst).unwrap();
         assert!(buf.is_empty());
-        assert_eq!(buf.size(), 0);
-        assert_eq!(read_result.unwrap(), 1);
+        assert_eq!(dst.iter().map(|rc| **rc).collect::<Vec<u8>>(), vec![0,0,1]);
+        // moved, not cloned, and the old slots don't hold on to them
+        assert_eq!(Rc::strong_count(&value), 3);
+        assert!(buf.buffer.iter().all(|rc| !Rc::ptr_eq(rc, &value)));
+
+        // dst is not empty
+        buf.write(Rc::new(2)).unwrap();
+        assert_eq!(buf.move_into(&mut dst), Err("CircularBuffer destination is not empty"));
 
+        // dst is too small
+        buf.write(Rc::new(3)).unwrap();
+        let mut small = CircularBuffer::<Rc<u8>>::new(1);
+        assert!(buf.move_into(&mut small).is_err());
+        assert_eq!(buf.size(), 2);
+        assert!(small.is_empty());
     }
 
     #[test]
-    fn test_basic_peek_many() {
+    #[allow(clippy::reversed_empty_ranges)]
+    fn test_peek_range() {
+        let mut buf = CircularBuffer::<u8>::new(6);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(4).unwrap();
+        buf.write_many(&[1,2,3,4,5,6]).unwrap();
 
+        assert_eq!(buf.peek_range(2..5), Ok(vec![3,4,5]));
+        assert_eq!(buf.peek_range(0..2), Ok(vec![1,2]));
+        assert_eq!(buf.peek_range(0..6), Ok(vec![1,2,3,4,5,6]));
+        assert_eq!(buf.peek_range(3..3), Ok(vec![]));
+        assert_eq!(buf.peek_range(4..7), Err("CircularBuffer range out of bounds"));
+        assert_eq!(buf.peek_range(4..2), Err("CircularBuffer range out of bounds"));
+        assert_eq!(buf.size(), 6);
+    }
+
+    #[test]
+    fn test_frequency() {
+        let mut buf = CircularBuffer::<char>::new(6);
+        buf.write_many(&['a','a','a','a']).unwrap();
+        buf.read_many(4).unwrap();
+        buf.write_many(&['b','c','b','d','b']).unwrap();
+
+        // only the elements are counted, not the unused slot (which holds the default '\0')
+        let counts = buf.frequency();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&'b'], 3);
+        assert_eq!(counts[&'c'], 1);
+        assert_eq!(counts[&'d'], 1);
+        assert!(!counts.contains_key(&'\0'));
+
+        assert!(CircularBuffer::<char>::new(3).frequency().is_empty());
+    }
+
+    #[test]
+    fn test_is_contiguous() {
         let mut buf = CircularBuffer::<u8>::new(4);
-        buf.write_many(&[1,2,3]).unwrap();
+        assert!(buf.is_contiguous());
+        buf.write_many(&[1,2,3,4]).unwrap();
+        assert!(buf.is_contiguous());
+        buf.read_many(3).unwrap();
+        buf.write(5).unwrap();
+        assert!(!buf.is_contiguous());
+        assert_eq!(buf.peek_chunk(2), None);
+        buf.read().unwrap();
+        assert!(buf.is_contiguous());
+        assert_eq!(buf.as_slices(), (&[5][..], &[][..]));
+        buf.read().unwrap();
+        assert!(buf.is_contiguous());
 
-        // can't read 4
-        let mut peek_result = buf.peek_many(4);
-        assert!(peek_result.is_err());
-        assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.size(), 3);
+        assert!(CircularBuffer::<u8>::new(0).is_contiguous());
+    }
 
-        peek_result = buf.peek_many(2);
-        assert!(peek_result.is_ok());
-        assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.size(), 3);
+    #[test]
+    fn test_rotate_overlapping_runs() {
+        // every combination of start offset, size and rotation, including almost full buffers, where the moved
+        // elements land on slots that overlap with the run they come from
+        let capacity = 7;
+        for offset in 0..capacity {
+            for size in 0..=capacity {
+                for n in 0..=size * 2 {
+                    let mut buf = CircularBuffer::<String>::new(capacity);
+                    buf.write_many(&vec![String::new(); offset]).unwrap();
+                    buf.read_many(offset).unwrap();
+                    let values: Vec<String> = (0..size).map(|i| i.to_string()).collect();
+                    buf.write_many(&values).unwrap();
 
-        let values = peek_result.unwrap();
-        assert_eq!(values.len(), 2);
-        assert_eq!(values[0], 1);
-        assert_eq!(values[1], 2);
+                    let mut expected = values.clone();
+                    if size > 0 {
+                        expected.rotate_left(n % size);
+                    }
+                    buf.rotate_left(n);
+                    assert_eq!(buf, expected, "rotate_left({}) with offset {} and size {}", n, offset, size);
 
+                    buf.rotate_right(n);
+                    assert_eq!(buf, values, "rotate_right({}) with offset {} and size {}", n, offset, size);
+
+                    // the free slots only hold default values, nothing was duplicated into them
+                    assert_eq!(buf.buffer.iter().filter(|value| !value.is_empty()).count(), size);
+                }
+            }
+        }
     }
 
     #[test]
-    fn test_basic_write_many() {
+    fn test_vec_deque() {
+        use std::collections::VecDeque;
+
         let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
 
-        let mut write_result = buf.write_many(&[1,2,3]);
-        assert!(write_result.is_ok());
-        assert_eq!(buf.is_empty(), false);
+        let deque = VecDeque::from(buf);
+        assert_eq!(deque, VecDeque::from(vec![1,2,3]));
+
+        let round_trip = CircularBuffer::from_vec_deque(4, deque.clone()).unwrap();
+        assert_eq!(round_trip.capacity(), 4);
+        assert_eq!(round_trip, vec![1,2,3]);
+        assert_eq!(VecDeque::from(round_trip), deque);
+
+        assert_eq!(CircularBuffer::from_vec_deque(2, deque).unwrap_err(),
+            "CircularBuffer does not have enough space for the provided elements");
+    }
+
+    #[test]
+    fn test_memory_footprint() {
+        assert_eq!(CircularBuffer::<u32>::element_size(), 4);
+        assert_eq!(CircularBuffer::<u32>::new(10).memory_footprint(), 40);
+        assert_eq!(CircularBuffer::<u8>::new(0).memory_footprint(), 0);
+        assert_eq!(CircularBuffer::<()>::new(10).memory_footprint(), 0);
+
+        // the capacity of an aligned buffer is rounded up, and so is the footprint
+        let aligned = CircularBuffer::<u16>::new_aligned(3, 64);
+        assert_eq!(aligned.memory_footprint(), 64);
+    }
+
+    #[test]
+    fn test_try_read_exact() {
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[1,2,3]).unwrap();
+        assert_eq!(buf.try_read_exact(4), None);
         assert_eq!(buf.size(), 3);
+        assert_eq!(buf.try_read_exact(2), Some(vec![1,2]));
+        buf.write_many(&[4,5]).unwrap();
+        assert_eq!(buf.try_read_exact(3), Some(vec![3,4,5]));
+        assert_eq!(buf.try_read_exact(0), Some(vec![]));
+        assert_eq!(buf.try_read_exact(1), None);
+    }
 
-        let mut read_result = buf.read();
-        assert!(read_result.is_ok());
-        assert_eq!(buf.size(), 2);
-        assert_eq!(read_result.unwrap(), 1);
+    #[test]
+    fn test_from_fn() {
+        let buf = CircularBuffer::from_fn(5, 3, |i| i * 10).unwrap();
+        assert_eq!(buf.capacity(), 5);
+        assert_eq!(buf, vec![0,10,20]);
 
-        read_result = buf.read();
-        assert!(read_result.is_ok());
-        assert_eq!(buf.size(), 1);
-        assert_eq!(read_result.unwrap(), 2);
+        assert!(CircularBuffer::from_fn(3, 3, |i| i).unwrap().is_full());
+        assert!(CircularBuffer::from_fn(3, 0, |i| i).unwrap().is_empty());
 
-        read_result = buf.read();
-        assert!(read_result.is_ok());
-        assert_eq!(buf.size(), 0);
-        assert_eq!(read_result.unwrap(), 3);
+        let mut called = false;
+        assert!(CircularBuffer::from_fn(2, 3, |i| {
+            called = true;
+            i
+        }).is_err());
+        assert!(!called);
+    }
+
+    #[test]
+    fn test_head_slice() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        assert_eq!(buf.head_slice(), &[]);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
+
+        let mut runs = Vec::new();
+        while !buf.is_empty() {
+            let run = buf.head_slice().to_vec();
+            buf.read_many(run.len()).unwrap();
+            runs.push(run);
+        }
+        assert_eq!(runs, vec![vec![1,2], vec![3,4]]);
+        assert_eq!(buf.head_slice(), &[]);
+    }
 
+    #[test]
+    fn test_overflow_policy() {
+        use crate::{DropNewest, ErrorPolicy, Overflow, OverflowPolicy};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let mut buf = CircularBuffer::<u8>::new(3);
+        buf.set_overflow_policy(DropNewest);
+        assert!(!buf.is_overwriting());
+        buf.write_many(&[1,2]).unwrap();
+        buf.write_many(&[3,4,5]).unwrap();
+        buf.write(6).unwrap();
+        assert_eq!(buf, vec![1,2,3]);
+        assert_eq!(buf.dropped_count(), 3);
+
+        // the clone gets its own copy of the policy
+        let mut clone = buf.clone();
+        assert!(clone.write(7).is_ok());
+        assert_eq!(clone, vec![1,2,3]);
+
+        buf.set_overflow_policy(ErrorPolicy);
+        assert!(buf.write(7).is_err());
+        assert!(buf.write_many(&[7]).is_err());
+
+        // a custom policy that overwrites, and counts how often that happened
+        #[derive(Clone)]
+        struct Counting(Arc<AtomicUsize>);
+
+        impl OverflowPolicy for Counting {
+            fn on_overflow(&mut self) -> Overflow {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Overflow::EvictOldest
+            }
+        }
+
+        let overflows = Arc::new(AtomicUsize::new(0));
+        buf.set_overflow_policy(Counting(Arc::clone(&overflows)));
+        buf.write(4).unwrap();
+        buf.write_many(&[5,6]).unwrap();
+        buf.write_many(&[7]).unwrap();
+        assert_eq!(buf, vec![5,6,7]);
+        assert_eq!(overflows.load(Ordering::Relaxed), 3);
+
+        // nothing is asked while there is enough space
+        buf.read_many(2).unwrap();
+        buf.write_many(&[8,9]).unwrap();
+        assert_eq!(overflows.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_shift_out() {
+        use std::rc::Rc;
+
+        let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4]).unwrap();
+        buf.shift_out(2).unwrap();
+        assert_eq!(buf, vec![3,4]);
+        assert_eq!(buf.shift_out(3), Err("CircularBuffer does not contain the amount of requested elements"));
+        assert_eq!(buf, vec![3,4]);
+        buf.shift_out(0).unwrap();
+        buf.shift_out(2).unwrap();
         assert!(buf.is_empty());
 
-        // buffer is now empty, try to add more that allowed
-        write_result = buf.write_many(&[1,2,3,4,5]);
-        assert!(write_result.is_err());
+        let value = Rc::new(0);
+        let mut rcs = CircularBuffer::<Rc<u8>>::new(3);
+        rcs.write_many(&[Rc::clone(&value), Rc::clone(&value), Rc::new(1)]).unwrap();
+        rcs.shift_out(2).unwrap();
+        assert_eq!(Rc::strong_count(&value), 1);
+        assert_eq!(*rcs[0], 1);
+    }
+
+    #[test]
+    fn test_write_all_iter() {
+        let mut buf = CircularBuffer::<u32>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+
+        buf.write_all_iter((1..4).map(|value| value * 10)).unwrap();
+        assert_eq!(buf, vec![10,20,30]);
+
+        let mut iter = vec![40, 50].into_iter();
+        assert_eq!(buf.write_all_iter(&mut iter), Err("CircularBuffer does not have enough space for the provided elements"));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(buf, vec![10,20,30]);
+
+        buf.write_all_iter(Vec::new()).unwrap();
+        buf.write_all_iter([40]).unwrap();
+        assert_eq!(buf, vec![10,20,30,40]);
+    }
+
+    #[test]
+    fn test_clear_drops_only_when_needed() {
+        use std::cell::Cell;
+
+        thread_local!(static DROPS: Cell<usize> = const { Cell::new(0) });
+
+        #[derive(Default, Clone)]
+        struct Counted(u8);
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                if self.0 != 0 {
+                    DROPS.with(|drops| drops.set(drops.get() + 1));
+                }
+            }
+        }
+
+        let mut buf = CircularBuffer::<Counted>::new(4);
+        for value in 1..=3 {
+            buf.write(Counted(value)).unwrap();
+        }
+        buf.clear();
         assert!(buf.is_empty());
-        assert_eq!(buf.size(), 0);
+        assert_eq!(DROPS.with(Cell::get), 3);
 
+        // u8 has no destructor, so clearing only resets the indices and leaves the old values in their slots
+        let mut bytes = CircularBuffer::<u8>::new(4);
+        bytes.write_many(&[1,2,3]).unwrap();
+        bytes.clear();
+        assert!(bytes.is_empty());
+        assert_eq!(bytes.layout(), (0, 0, 0));
+        assert_eq!(&bytes.buffer[..3], &[1,2,3]);
     }
 
     #[test]
-    fn test_basic_read_many() {
+    fn test_available_contiguous_write() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        assert_eq!(buf.available_contiguous_write(), 5);
+        buf.write_many(&[1,2,3]).unwrap();
+        assert_eq!(buf.available_contiguous_write(), 2);
+        buf.read_many(2).unwrap();
+        // the free space wraps around: 2 slots at the end, 2 at the start
+        assert_eq!(buf.available_contiguous_write(), 2);
+        buf.write_many(&[4,5]).unwrap();
+        assert_eq!(buf.available_contiguous_write(), 2);
+        buf.write_many(&[6,7]).unwrap();
+        assert_eq!(buf.available_contiguous_write(), 0);
+
+        assert_eq!(CircularBuffer::<u8>::new(0).available_contiguous_write(), 0);
+    }
+
+    #[test]
+    fn test_read_tap() {
+        let mut buf = CircularBuffer::<u8>::new(3);
+        buf.write_many(&[1,2]).unwrap();
+
+        let mut seen = Vec::new();
+        assert_eq!(buf.read_tap(|value| seen.push(*value)), Ok(1));
+        assert_eq!(buf.read_tap(|value| seen.push(*value)), Ok(2));
+        assert_eq!(buf.read_tap(|value| seen.push(*value)), Err("CircularBuffer is empty"));
+        assert_eq!(seen, vec![1,2]);
+    }
+
+    #[test]
+    fn test_resize_with() {
         let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
         buf.write_many(&[1,2,3]).unwrap();
 
-        // can't read 4
-        let mut read_result = buf.read_many(4);
-        assert!(read_result.is_err());
-        assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.size(), 3);
+        let mut next = 10;
+        buf.resize_with(6, || {
+            next += 1;
+            next
+        });
+        assert_eq!(buf.capacity(), 6);
+        assert_eq!(buf, vec![1,2,3,11,12,13]);
 
-        read_result = buf.read_many(2);
-        assert!(read_result.is_ok());
-        assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.size(), 1);
-        let boxed_values = read_result.unwrap();
-        let values = boxed_values.deref();
-        assert_eq!(values.len(), 2);
-        assert_eq!(values[0], 1);
-        assert_eq!(values[1], 2);
+        // shrinking keeps the newest elements, and there is nothing left to fill
+        buf.resize_with(2, || unreachable!());
+        assert_eq!(buf, vec![12,13]);
     }
 
     #[test]
-    fn test_basic_clear() {
+    fn test_to_vec_from() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3,4,5]).unwrap();
+
+        assert_eq!(buf.to_vec_from(0), vec![1,2,3,4,5]);
+        // starts before the wrap, ends after it
+        assert_eq!(buf.to_vec_from(1), vec![2,3,4,5]);
+        // starts after the wrap
+        assert_eq!(buf.to_vec_from(3), vec![4,5]);
+        assert_eq!(buf.to_vec_from(3), buf.peek_range(3..5).unwrap());
+        assert_eq!(buf.to_vec_from(5), Vec::<u8>::new());
+        assert_eq!(buf.to_vec_from(9), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_is_sorted() {
+        let mut buf = CircularBuffer::<u32>::new(5);
+        assert!(buf.is_sorted());
+        buf.write_many(&[9,9,9]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write(1).unwrap();
+        assert!(buf.is_sorted());
+
+        // the elements wrap around the end of the internal buffer
+        buf.write_many(&[2,2,5,8]).unwrap();
+        assert!(buf.is_sorted());
+        assert_eq!(buf.binary_search(&5), Ok(3));
+
+        buf.read().unwrap();
+        buf.write(3).unwrap();
+        assert!(!buf.is_sorted());
+
+        let mut floats = CircularBuffer::<f64>::new(2);
+        floats.write_many(&[1.0, f64::NAN]).unwrap();
+        assert!(!floats.is_sorted());
+    }
+
+    #[test]
+    fn test_merge_sorted() {
+        let mut a = CircularBuffer::<u32>::new(4);
+        a.write_many(&[0,0,0]).unwrap();
+        a.read_many(3).unwrap();
+        a.write_many(&[1,4,6,9]).unwrap();
+
+        let mut b = CircularBuffer::<u32>::new(5);
+        b.write_many(&[2,4,5]).unwrap();
+
+        let merged = a.merge_sorted(&b);
+        assert_eq!(merged, vec![1,2,4,4,5,6,9]);
+        assert_eq!(merged.capacity(), 7);
+        assert!(merged.is_full());
+        assert!(merged.is_sorted());
+
+        let empty = CircularBuffer::<u32>::new(3);
+        assert_eq!(empty.merge_sorted(&b), b);
+        assert_eq!(b.merge_sorted(&empty), b);
+        assert!(empty.merge_sorted(&empty).is_empty());
+    }
+
+    #[test]
+    fn test_as_ptr() {
         let mut buf = CircularBuffer::<u8>::new(4);
+        buf.write_many(&[0,0,0]).unwrap();
+        buf.read_many(3).unwrap();
+        buf.write_many(&[1,2,3]).unwrap();
 
-        let write_result = buf.write_many(&[1,2,3]);
-        assert!(write_result.is_ok());
-        assert_eq!(buf.is_empty(), false);
-        assert_eq!(buf.size(), 3);
+        // reconstruct the elements from the raw pointer and the layout, like C code would
+        let (start, _, size) = buf.layout();
+        let capacity = buf.capacity();
+        let ptr = buf.as_ptr();
+        let elements: Vec<u8> = (0..size).map(|i| unsafe { *ptr.add((start + i) % capacity) }).collect();
+        assert_eq!(elements, vec![1,2,3]);
 
-        buf.clear();
-        assert!(buf.is_empty());
-        assert_eq!(buf.size(), 0);
+        let ptr = buf.as_mut_ptr();
+        unsafe { *ptr.add((start + 1) % capacity) = 20 };
+        assert_eq!(buf, vec![1,20,3]);
+    }
+
+    #[test]
+    fn test_fill_from_limited() {
+        let mut buf = CircularBuffer::<u8>::new(5);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(4).unwrap();
+
+        let mut input: &[u8] = &[1,2,3,4,5,6,7];
+        assert_eq!(buf.fill_from_limited(&mut input, 3).unwrap(), 3);
+        assert_eq!(buf, vec![1,2,3]);
+        assert_eq!(buf.fill_from_limited(&mut input, 0).unwrap(), 0);
+
+        // limited by the free space
+        assert_eq!(buf.fill_from_limited(&mut input, 10).unwrap(), 2);
+        assert_eq!(buf, vec![1,2,3,4,5]);
+        assert_eq!(buf.fill_from_limited(&mut input, 10).unwrap(), 0);
+
+        // limited by the end of the reader
+        buf.read_many(5).unwrap();
+        assert_eq!(buf.fill_from_limited(&mut input, 4).unwrap(), 2);
+        assert_eq!(buf.fill_from_limited(&mut input, 4).unwrap(), 0);
+        assert_eq!(buf, vec![6,7]);
+    }
+
+    #[test]
+    fn test_starts_ends_with() {
+        let mut buf = CircularBuffer::<u8>::new(6);
+        buf.write_many(&[0,0,0,0]).unwrap();
+        buf.read_many(4).unwrap();
+        buf.write_many(b"GET\r\n").unwrap();
+
+        // both patterns cross the wrap
+        assert!(buf.starts_with(b"GET"));
+        assert!(buf.ends_with(b"T\r\n"));
+        assert!(buf.starts_with(b"GET\r\n"));
+        assert!(buf.ends_with(b"GET\r\n"));
+        assert!(buf.starts_with(b""));
+        assert!(buf.ends_with(b""));
+
+        assert!(!buf.starts_with(b"PUT"));
+        assert!(!buf.ends_with(b"\n\n"));
+        assert!(!buf.starts_with(b"GET\r\n\r"));
+        assert!(!buf.ends_with(b"xGET\r\n"));
 
+        let empty = CircularBuffer::<u8>::new(0);
+        assert!(empty.starts_with(&[]));
+        assert!(!empty.ends_with(&[1]));
     }
 
     #[test]
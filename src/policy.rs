@@ -0,0 +1,71 @@
+use core::any::Any;
+
+// what happens to the new elements when they are written to a full buffer, decided by an OverflowPolicy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    // the write fails with an error, and the buffer stays the same
+    Reject,
+    // the oldest elements are dropped to make room for the new ones
+    EvictOldest,
+    // the new elements that don't fit are dropped, and the write succeeds anyway, see CircularBuffer::dropped_count()
+    DropNewest
+}
+
+// decides what write() and write_many() do when there is not enough space, see CircularBuffer::set_overflow_policy()
+// on_overflow() is called once for every write that doesn't fit, so a policy can also keep track of how often that
+// happens (e.g. to log it), a policy must be Clone so that a cloned buffer gets its own copy,
+// and Send + Sync so that the buffer can still be shared with and moved to other threads
+pub trait OverflowPolicy: Send + Sync + 'static {
+
+    fn on_overflow(&mut self) -> Overflow;
+
+}
+
+// the default policy, writing to a full buffer fails
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ErrorPolicy;
+
+impl OverflowPolicy for ErrorPolicy {
+    fn on_overflow(&mut self) -> Overflow {
+        Overflow::Reject
+    }
+}
+
+// overwrites the oldest elements, see CircularBuffer::new_overwriting()
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OverwriteOldest;
+
+impl OverflowPolicy for OverwriteOldest {
+    fn on_overflow(&mut self) -> Overflow {
+        Overflow::EvictOldest
+    }
+}
+
+// keeps the elements that are already in the buffer, and drops the new ones that don't fit
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DropNewest;
+
+impl OverflowPolicy for DropNewest {
+    fn on_overflow(&mut self) -> Overflow {
+        Overflow::DropNewest
+    }
+}
+
+// private extension of OverflowPolicy, so a boxed policy can still be cloned and inspected
+pub(crate) trait BoxedPolicy: OverflowPolicy {
+
+    fn clone_box(&self) -> Box<dyn BoxedPolicy>;
+
+    fn as_any(&self) -> &dyn Any;
+
+}
+
+impl<P: OverflowPolicy + Clone> BoxedPolicy for P {
+    fn clone_box(&self) -> Box<dyn BoxedPolicy> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
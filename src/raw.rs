@@ -0,0 +1,131 @@
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use core::{mem, slice};
+use std::alloc::{self, Layout};
+
+// fixed-size heap allocation that holds the elements of a CircularBuffer
+// it behaves like a Box<[T]>, except that it can be allocated with a larger alignment than T requires
+// every slot is always initialised, just like the Box<[T]> it replaces
+// this is the default storage of a CircularBuffer, see Storage
+pub struct RawBuffer<T> {
+    ptr: NonNull<T>,
+    len: usize,
+    // alignment (in bytes) the allocation was made with, needed to deallocate it again
+    align: usize,
+    // tells the compiler we own values of type T, which matters for drop checking
+    _marker: PhantomData<T>
+}
+
+// RawBuffer owns its elements, so it is Send/Sync under the same conditions as Box<[T]>
+unsafe impl<T: Send> Send for RawBuffer<T> {}
+unsafe impl<T: Sync> Sync for RawBuffer<T> {}
+
+impl<T> RawBuffer<T> {
+
+    // allocates room for len elements, aligned to at least `align` bytes, and initialises every slot with f()
+    // align must be a power of two
+    // if f panics, the allocation (and the elements created so far) are leaked, but no undefined behaviour occurs
+    pub(crate) fn new_with<F: FnMut() -> T>(len: usize, align: usize, f: F) -> Self {
+        let align = align.max(mem::align_of::<T>());
+        let layout = Self::layout(len, align);
+        let ptr = Self::allocate(layout).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        Self::init(ptr, len, align, f)
+    }
+
+    // same as new_with(), but returns an error instead of panicking/aborting when the allocation is too large
+    // or can't be satisfied
+    pub(crate) fn try_new_with<F: FnMut() -> T>(len: usize, align: usize, f: F) -> Result<Self, &'static str> {
+        let align = align.max(mem::align_of::<T>());
+        let layout = Self::try_layout(len, align).ok_or("CircularBuffer capacity overflow")?;
+        let ptr = Self::allocate(layout).ok_or("CircularBuffer allocation failed")?;
+        Ok(Self::init(ptr, len, align, f))
+    }
+
+    // returns None if the allocator can't satisfy the layout
+    fn allocate(layout: Layout) -> Option<NonNull<T>> {
+        if layout.size() == 0 {
+            // nothing to allocate, any well-aligned non-null pointer will do
+            NonNull::new(ptr::without_provenance_mut(layout.align()))
+        } else {
+            NonNull::new(unsafe { alloc::alloc(layout) } as *mut T)
+        }
+    }
+
+    // initialises every slot of a fresh allocation with f()
+    fn init<F: FnMut() -> T>(ptr: NonNull<T>, len: usize, align: usize, mut f: F) -> Self {
+        for i in 0..len {
+            unsafe { ptr.as_ptr().add(i).write(f()) };
+        }
+
+        Self {
+            ptr,
+            len,
+            align,
+            _marker: PhantomData
+        }
+    }
+
+    // alignment (in bytes) the allocation was made with
+    pub(crate) fn alignment(&self) -> usize {
+        self.align
+    }
+
+    fn layout(len: usize, align: usize) -> Layout {
+        Self::try_layout(len, align).expect("CircularBuffer capacity overflow")
+    }
+
+    fn try_layout(len: usize, align: usize) -> Option<Layout> {
+        mem::size_of::<T>().checked_mul(len)
+            .and_then(|size| Layout::from_size_align(size, align).ok())
+    }
+
+}
+
+// takes over the allocation of the box, without copying the elements
+impl<T> From<Box<[T]>> for RawBuffer<T> {
+    fn from(values: Box<[T]>) -> Self {
+        let len = values.len();
+        let raw = Box::into_raw(values) as *mut T;
+        Self {
+            ptr: NonNull::new(raw).unwrap(),
+            len,
+            align: mem::align_of::<T>(),
+            _marker: PhantomData
+        }
+    }
+}
+
+// clones every slot into a new allocation with the same alignment
+impl<T: Clone> Clone for RawBuffer<T> {
+    fn clone(&self) -> Self {
+        let mut slots = self.iter();
+        Self::new_with(self.len, self.align, || slots.next().unwrap().clone())
+    }
+}
+
+impl<T> Deref for RawBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for RawBuffer<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Drop for RawBuffer<T> {
+    fn drop(&mut self) {
+        let layout = Self::layout(self.len, self.align);
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len));
+            if layout.size() != 0 {
+                alloc::dealloc(self.ptr.as_ptr() as *mut u8, layout);
+            }
+        }
+    }
+}
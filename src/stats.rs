@@ -0,0 +1,138 @@
+use core::ops::{Add, Sub};
+
+use crate::CircularBuffer;
+
+// numeric buffer that keeps a running sum of its elements, so sum() and mean() are O(1) instead of O(size)
+// the sum is updated on every write (adding the new element) and every read or eviction (subtracting the old one),
+// that's why the buffer itself is only exposed read-only, see as_buffer()
+// for floating point types the running sum can drift slightly from the exact sum of the current elements,
+// for integer types it's exact, but (like CircularBuffer::sum()) it overflows if the sum doesn't fit into T
+pub struct StatsCircularBuffer<T> {
+    buffer: CircularBuffer<T>,
+    sum: T
+}
+
+impl<T> StatsCircularBuffer<T> where T: Default + Copy + Add<Output = T> + Sub<Output = T> {
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffer: CircularBuffer::new(capacity),
+            sum: T::default()
+        }
+    }
+
+    // writes an element, fails if the buffer is full, like CircularBuffer::write()
+    pub fn write(&mut self, value: T) -> Result<(), &'static str> {
+        self.buffer.write(value)?;
+        self.sum = self.sum + value;
+        Ok(())
+    }
+
+    // writes an element, overwriting (and returning) the oldest element if the buffer is full,
+    // like CircularBuffer::write_overwrite(), the overwritten element is subtracted from the sum
+    pub fn write_overwrite(&mut self, value: T) -> Option<T> {
+        let evicted = self.buffer.write_overwrite(value);
+        if self.buffer.capacity() == 0 {
+            // nothing can be stored, the new element was evicted right away
+            return evicted;
+        }
+        if let Some(old_value) = evicted {
+            self.sum = self.sum - old_value;
+        }
+        self.sum = self.sum + value;
+        evicted
+    }
+
+    pub fn read(&mut self) -> Result<T, &'static str> {
+        let value = self.buffer.read()?;
+        self.sum = self.sum - value;
+        Ok(value)
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.sum = T::default();
+    }
+
+    // returns the sum of all elements, or T::default() (zero for numeric types) if the buffer is empty
+    pub fn sum(&self) -> T {
+        self.sum
+    }
+
+    // returns the average of all elements, or None if the buffer is empty
+    pub fn mean(&self) -> Option<f64> where T: Into<f64> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+        Some(self.sum.into() / self.buffer.size() as f64)
+    }
+
+    // read-only access to the underlying buffer, e.g. for peek() or iter()
+    pub fn as_buffer(&self) -> &CircularBuffer<T> {
+        &self.buffer
+    }
+
+    pub fn size(&self) -> usize {
+        self.buffer.size()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.buffer.is_full()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::StatsCircularBuffer;
+
+    #[test]
+    fn test_running_sum() {
+        let mut buf = StatsCircularBuffer::<u32>::new(3);
+        assert_eq!(buf.sum(), 0);
+        assert_eq!(buf.mean(), None);
+
+        buf.write(1).unwrap();
+        buf.write(2).unwrap();
+        buf.write(3).unwrap();
+        assert!(buf.write(4).is_err());
+        assert_eq!(buf.sum(), 6);
+        assert_eq!(buf.mean(), Some(2.0));
+
+        assert_eq!(buf.read().unwrap(), 1);
+        assert_eq!(buf.sum(), 5);
+
+        buf.clear();
+        assert_eq!(buf.sum(), 0);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_running_sum_overwrite() {
+        let mut buf = StatsCircularBuffer::<i32>::new(3);
+        for value in 1..=10 {
+            buf.write_overwrite(value);
+            // the running sum always matches the sum of the elements that are still inside
+            assert_eq!(buf.sum(), buf.as_buffer().sum());
+        }
+        assert_eq!(buf.sum(), 8 + 9 + 10);
+        assert_eq!(buf.mean(), Some(9.0));
+        assert_eq!(buf.size(), 3);
+        assert!(buf.is_full());
+
+        let mut empty = StatsCircularBuffer::<i32>::new(0);
+        assert_eq!(empty.write_overwrite(1), Some(1));
+        assert_eq!(empty.sum(), 0);
+        assert_eq!(empty.capacity(), 0);
+    }
+
+}
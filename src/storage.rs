@@ -0,0 +1,129 @@
+use core::ops::{Deref, DerefMut};
+
+use crate::raw::RawBuffer;
+#[cfg(feature = "heapless")]
+use crate::CircularBuffer;
+
+// the memory that holds the elements of a CircularBuffer, every slot is always initialised
+// the buffer only accesses its storage as a slice, so any fixed-size container that derefs to [T] can be used,
+// see CircularBuffer::from_storage()
+// RawBuffer (a heap allocation) is the default, the heapless feature adds a backend that doesn't allocate
+//
+// Safety: implementors must guarantee that deref() and deref_mut() always return the same stable slice of capacity
+// initialised elements, where capacity is the length of the slice when the storage is handed to the buffer: its
+// length never changes, and its elements only change through deref_mut() (it may move along with the storage
+// itself), the storage returned by reallocated(len) must hold exactly len initialised elements
+// the buffer caches the capacity and indexes the slice without bounds checks, so breaking this is undefined behavior
+#[allow(clippy::missing_safety_doc)]
+pub unsafe trait Storage<T>: Deref<Target = [T]> + DerefMut + Sized {
+
+    // returns a new storage of the same kind that holds len default elements, used when the capacity changes
+    // returns None if this kind of storage can't hold len elements, in which case the capacity stays the same
+    fn reallocated(&self, len: usize) -> Option<Self> where T: Default;
+
+}
+
+// the new allocation keeps the alignment of the old one, but not a size that is a multiple of it, see
+// CircularBuffer::new_aligned()
+unsafe impl<T> Storage<T> for RawBuffer<T> {
+    fn reallocated(&self, len: usize) -> Option<Self> where T: Default {
+        Some(RawBuffer::new_with(len, self.alignment(), T::default))
+    }
+}
+
+// the elements are stored inline, so the capacity can change, but it can never be larger than N
+#[cfg(feature = "heapless")]
+unsafe impl<T, const N: usize> Storage<T> for heapless::Vec<T, N> {
+    fn reallocated(&self, len: usize) -> Option<Self> where T: Default {
+        if len > N {
+            return None;
+        }
+        let mut storage = heapless::Vec::new();
+        for _ in 0..len {
+            // can't fail, we checked above that len elements fit
+            let _ = storage.push(T::default());
+        }
+        Some(storage)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<T: Default + Clone, const N: usize> CircularBuffer<T, heapless::Vec<T, N>> {
+
+    // creates an empty buffer with a capacity of N, without allocating
+    pub fn new_heapless() -> Self {
+        Self::from_storage(heapless::Vec::new().reallocated(N).unwrap())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use core::ops::{Deref, DerefMut};
+
+    use crate::{CircularBuffer, Storage};
+
+    // minimal custom backend, that can't change its capacity
+    struct FixedVec(Vec<u8>);
+
+    impl Deref for FixedVec {
+        type Target = [u8];
+
+        fn deref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl DerefMut for FixedVec {
+        fn deref_mut(&mut self) -> &mut [u8] {
+            &mut self.0
+        }
+    }
+
+    // the inner vec is never resized
+    unsafe impl Storage<u8> for FixedVec {
+        fn reallocated(&self, _len: usize) -> Option<Self> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_custom_storage() {
+        let mut buf = CircularBuffer::from_storage(FixedVec(vec![9; 3]));
+        assert_eq!(buf.capacity(), 3);
+        assert!(buf.is_empty());
+        buf.write_many(&[1,2]).unwrap();
+        buf.read().unwrap();
+        buf.write_many(&[3,4]).unwrap();
+        assert_eq!(buf, vec![2,3,4]);
+        assert_eq!(buf.iter().copied().collect::<Vec<u8>>(), vec![2,3,4]);
+        assert_eq!(buf.into_iter().rev().collect::<Vec<u8>>(), vec![4,3,2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "storage can't hold")]
+    fn test_custom_storage_resize() {
+        let mut buf = CircularBuffer::from_storage(FixedVec(vec![0; 3]));
+        buf.resize_keep_newest(4);
+    }
+
+    #[test]
+    #[cfg(feature = "heapless")]
+    fn test_heapless() {
+        let mut buf = CircularBuffer::<u8, heapless::Vec<u8, 4>>::new_heapless();
+        assert_eq!(buf.capacity(), 4);
+        buf.write_many(&[1,2,3]).unwrap();
+        buf.read_many(2).unwrap();
+        buf.write_many(&[4,5,6]).unwrap();
+        assert_eq!(buf, vec![3,4,5,6]);
+        assert!(buf.write(7).is_err());
+        assert_eq!(buf.to_string(), "[5,6,3,4]");
+
+        // shrinking works, but it can't grow beyond the inline capacity
+        buf.resize_keep_newest(2);
+        assert_eq!(buf.capacity(), 2);
+        assert_eq!(buf, vec![5,6]);
+    }
+
+}
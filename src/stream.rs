@@ -0,0 +1,81 @@
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::SyncCircularBuffer;
+
+// futures::Stream that pops the elements of a SyncCircularBuffer as they are pushed, see SyncCircularBuffer::stream()
+// it ends when the buffer is closed and empty
+pub struct BufferStream<'a, T> {
+    buffer: &'a SyncCircularBuffer<T>
+}
+
+impl<'a, T> BufferStream<'a, T> {
+    pub(crate) fn new(buffer: &'a SyncCircularBuffer<T>) -> Self {
+        Self {
+            buffer
+        }
+    }
+}
+
+impl<'a, T: Default + Clone> Stream for BufferStream<'a, T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        // register before checking, so a push that happens in between still wakes us up
+        self.buffer.register_waker(cx.waker());
+        if let Ok(value) = self.buffer.pop() {
+            return Poll::Ready(Some(value));
+        }
+        if self.buffer.is_closed() {
+            // check once more, an element could have been pushed right before the buffer was closed
+            return Poll::Ready(self.buffer.pop().ok());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+    use std::thread;
+
+    use futures::executor::block_on;
+    use futures::StreamExt;
+
+    use crate::SyncCircularBuffer;
+
+    #[test]
+    fn test_stream() {
+        let buf = SyncCircularBuffer::<u8>::new(4);
+        buf.push(1).unwrap();
+        buf.push(2).unwrap();
+        buf.close();
+        assert_eq!(block_on(buf.stream().collect::<Vec<u8>>()), vec![1, 2]);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_stream_wakes_consumer() {
+        let buf = Arc::new(SyncCircularBuffer::<u32>::new(4));
+
+        let producer = {
+            let buf = Arc::clone(&buf);
+            thread::spawn(move || {
+                for i in 0..100 {
+                    while buf.push(i).is_err() {
+                        thread::yield_now();
+                    }
+                }
+                buf.close();
+            })
+        };
+
+        let received = block_on(buf.stream().collect::<Vec<u32>>());
+        producer.join().unwrap();
+        assert_eq!(received, (0..100).collect::<Vec<u32>>());
+    }
+
+}
@@ -0,0 +1,74 @@
+use core::marker::PhantomData;
+use std::sync::Weak;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{CircularBuffer, Storage};
+
+// handle that follows the elements written to a buffer, see CircularBuffer::subscribe()
+// every subscription has its own read position, so multiple subscribers see the same elements without consuming them
+// it only holds a weak reference to the write counter of the buffer, so it doesn't keep anything alive
+//
+// this is meant for an overwrite ring (see write_overwrite()), where nobody reads from the buffer itself:
+// when a slow subscriber falls behind by more than the capacity, the elements it missed have already been overwritten,
+// the next pull() then continues from the oldest element still inside the buffer, and the amount of skipped
+// elements is added to missed()
+//
+// only elements written to the tail are followed, elements inserted with push_front() are never delivered
+// elements removed from the tail with pop_back() (or restore()) are not taken back from the write counter, so a
+// subscriber that hadn't pulled them yet gets the same amount of older elements in their place
+pub struct Subscription<T> {
+    written: Weak<AtomicU64>,
+    // sequence number of the next element this subscriber hasn't seen yet
+    position: u64,
+    // amount of elements that were overwritten before this subscriber could pull them
+    missed: u64,
+    _marker: PhantomData<fn() -> T>
+}
+
+impl<T: Default + Clone> Subscription<T> {
+
+    pub(crate) fn new(written: Weak<AtomicU64>, position: u64) -> Self {
+        Self {
+            written,
+            position,
+            missed: 0,
+            _marker: PhantomData
+        }
+    }
+
+    // returns true if elements were written since the last pull()
+    // this doesn't need access to the buffer, so it can be polled from another thread
+    // returns false if the buffer no longer exists
+    pub fn has_new(&self) -> bool {
+        match self.written.upgrade() {
+            Some(written) => written.load(Ordering::Acquire) > self.position,
+            None => false
+        }
+    }
+
+    // returns (clones of) all elements written since the last pull(), oldest first
+    // the buffer must be the one this subscription was created from
+    pub fn pull<S: Storage<T>>(&mut self, buffer: &CircularBuffer<T, S>) -> Vec<T> {
+        assert!(self.written.as_ptr() == buffer.written_ptr(), "Subscription belongs to a different CircularBuffer");
+
+        // the write counter never goes back, so the elements written since the last pull are the newest ones,
+        // as far as they are still inside the buffer
+        let written = buffer.written();
+        let size = buffer.size() as u64;
+        let mut new = written - self.position;
+        if new > size {
+            self.missed += new - size;
+            new = size;
+        }
+
+        let values = buffer.iter().skip((size - new) as usize).cloned().collect();
+        self.position = written;
+        values
+    }
+
+    // returns the total amount of elements that were overwritten before this subscriber could pull them
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+}
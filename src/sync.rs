@@ -0,0 +1,219 @@
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError, TryLockError};
+#[cfg(feature = "futures")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "futures")]
+use std::task::Waker;
+
+use crate::CircularBuffer;
+#[cfg(feature = "futures")]
+use crate::stream::BufferStream;
+
+// thread-safe wrapper around a buffer, for sharing it between (for instance) a producer and a consumer thread
+// every method locks an internal mutex for the duration of the call, so it can be used through a shared reference
+// (e.g. an Arc<SyncCircularBuffer<T>>), the buffer itself stays single-threaded and lock-free
+pub struct SyncCircularBuffer<T> {
+    buffer: Mutex<CircularBuffer<T>>,
+    // signalled every time an element is pushed, so blocking_pop() can wait for it
+    not_empty: Condvar,
+    // woken every time an element is pushed (or the buffer is closed), so a stream() can wait for it
+    #[cfg(feature = "futures")]
+    waker: Mutex<Option<Waker>>,
+    // set by close(), a stream() ends once it has yielded the remaining elements
+    #[cfg(feature = "futures")]
+    closed: AtomicBool
+}
+
+impl<T: Default + Clone> SyncCircularBuffer<T> {
+
+    pub fn new(capacity: usize) -> Self {
+        Self::from(CircularBuffer::new(capacity))
+    }
+
+    // writes an element, waiting for the lock if another thread holds it
+    // fails if the buffer is full, like CircularBuffer::write()
+    pub fn push(&self, value: T) -> Result<(), &'static str> {
+        let result = self.lock().write(value);
+        if result.is_ok() {
+            self.notify();
+        }
+        result
+    }
+
+    // same as push(), but fails right away instead of waiting if another thread holds the lock
+    pub fn try_push(&self, value: T) -> Result<(), &'static str> {
+        let result = self.try_lock()?.write(value);
+        if result.is_ok() {
+            self.notify();
+        }
+        result
+    }
+
+    // reads the oldest element, waiting for the lock if another thread holds it
+    // fails if the buffer is empty, like CircularBuffer::read()
+    pub fn pop(&self) -> Result<T, &'static str> {
+        self.lock().read()
+    }
+
+    // same as pop(), but fails right away instead of waiting if another thread holds the lock
+    pub fn try_pop(&self) -> Result<T, &'static str> {
+        self.try_lock()?.read()
+    }
+
+    // reads the oldest element, waiting until one is available if the buffer is empty
+    pub fn blocking_pop(&self) -> T {
+        let mut buffer = self.lock();
+        while buffer.is_empty() {
+            buffer = self.not_empty.wait(buffer).unwrap_or_else(PoisonError::into_inner);
+        }
+        buffer.read().unwrap()
+    }
+
+    // returns the amount of elements currently in the buffer
+    // other threads can push or pop right after this returns, so it's only a snapshot
+    pub fn len(&self) -> usize {
+        self.lock().size()
+    }
+
+    // returns true if the buffer is empty, with the same caveat as len()
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    // returns a futures::Stream that yields the elements in order as they are pushed, and ends after close()
+    // the stream pops the elements, so use only one consumer: if several streams wait at the same time,
+    // only the one that polled last is woken
+    #[cfg(feature = "futures")]
+    pub fn stream(&self) -> BufferStream<'_, T> {
+        BufferStream::new(self)
+    }
+
+    // signals the end of the stream, a stream() ends as soon as it has yielded the elements that are still buffered
+    // pushing is still possible afterwards, but a stream that already ended won't see those elements
+    #[cfg(feature = "futures")]
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.wake_stream();
+    }
+
+    #[cfg(feature = "futures")]
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    // registers the waker of the stream that is waiting for the next element, see BufferStream
+    #[cfg(feature = "futures")]
+    pub(crate) fn register_waker(&self, waker: &Waker) {
+        let mut registered = self.waker.lock().unwrap_or_else(PoisonError::into_inner);
+        match registered.as_ref() {
+            Some(current) if current.will_wake(waker) => {},
+            _ => *registered = Some(waker.clone())
+        }
+    }
+
+    // unwraps the buffer again, no locking is needed because this consumes the wrapper
+    pub fn into_inner(self) -> CircularBuffer<T> {
+        self.buffer.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // private function that wakes up whoever is waiting for the next element
+    fn notify(&self) {
+        self.not_empty.notify_one();
+        #[cfg(feature = "futures")]
+        self.wake_stream();
+    }
+
+    #[cfg(feature = "futures")]
+    fn wake_stream(&self) {
+        if let Some(waker) = self.waker.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            waker.wake();
+        }
+    }
+
+    // private function that locks the buffer, a panic in another thread can't leave the buffer in an invalid state
+    // (elements are only moved in and out), so a poisoned lock is simply taken over
+    fn lock(&self) -> MutexGuard<'_, CircularBuffer<T>> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // private function, non-blocking version of lock()
+    fn try_lock(&self) -> Result<MutexGuard<'_, CircularBuffer<T>>, &'static str> {
+        match self.buffer.try_lock() {
+            Ok(buffer) => Ok(buffer),
+            Err(TryLockError::Poisoned(poisoned)) => Ok(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => Err("SyncCircularBuffer is locked by another thread")
+        }
+    }
+
+}
+
+impl<T: Default + Clone> From<CircularBuffer<T>> for SyncCircularBuffer<T> {
+    fn from(buffer: CircularBuffer<T>) -> Self {
+        Self {
+            buffer: Mutex::new(buffer),
+            not_empty: Condvar::new(),
+            #[cfg(feature = "futures")]
+            waker: Mutex::new(None),
+            #[cfg(feature = "futures")]
+            closed: AtomicBool::new(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::{CircularBuffer, SyncCircularBuffer};
+
+    #[test]
+    fn test_push_pop() {
+        let buf = SyncCircularBuffer::<u8>::new(2);
+        assert!(buf.is_empty());
+        buf.push(1).unwrap();
+        buf.try_push(2).unwrap();
+        assert_eq!(buf.push(3), Err("CircularBuffer is full"));
+        assert_eq!(buf.len(), 2);
+        assert_eq!(buf.pop().unwrap(), 1);
+        assert_eq!(buf.try_pop().unwrap(), 2);
+        assert_eq!(buf.pop(), Err("CircularBuffer is empty"));
+    }
+
+    #[test]
+    fn test_try_lock_contended() {
+        let buf = SyncCircularBuffer::<u8>::new(2);
+        let _guard = buf.lock();
+        assert_eq!(buf.try_push(1), Err("SyncCircularBuffer is locked by another thread"));
+        assert_eq!(buf.try_pop(), Err("SyncCircularBuffer is locked by another thread"));
+    }
+
+    #[test]
+    fn test_blocking_pop() {
+        let buf = Arc::new(SyncCircularBuffer::<u32>::new(4));
+
+        let producer = {
+            let buf = Arc::clone(&buf);
+            thread::spawn(move || {
+                for i in 0..100 {
+                    while buf.push(i).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            })
+        };
+
+        let received: Vec<u32> = (0..100).map(|_| buf.blocking_pop()).collect();
+        producer.join().unwrap();
+        assert_eq!(received, (0..100).collect::<Vec<u32>>());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_from_into_inner() {
+        let buf = SyncCircularBuffer::from(CircularBuffer::from(vec![1u8, 2]));
+        assert_eq!(buf.len(), 2);
+        assert_eq!(buf.into_inner().read_many(2).unwrap(), vec![1, 2]);
+    }
+
+}
@@ -1,5 +1,5 @@
 
-use circular_buffer::CircularBuffer;
+use circular_buffer::{CircularBuffer, CircularBufferArray};
 
 // NOTE: most unit tests are in lib.rs, so that tests can check the state of private fields
 
@@ -15,10 +15,179 @@ fn test_check_methods() {
     buf.peek().unwrap();
     buf.read().unwrap();
     buf.size();
+    buf.len();
     buf.capacity();
     buf.write_many(&[1,2]).unwrap();
+    buf.read_many(2).unwrap();
+    buf.write_many_tracked(&[1,2]).unwrap();
     buf.peek_many(2).unwrap();
+    buf.contains(&1);
+    buf.clamp_all(1, 2);
+    buf.read_many(2).unwrap();
+    buf.unique();
+    buf.push_front(1).unwrap();
+    buf.read().unwrap();
+    buf.write_many(&[0,0]).unwrap();
+    buf.read_frame(2, 1).unwrap();
+    buf.write(1).unwrap();
+    buf.pop_back().unwrap();
+    unsafe {
+        buf.write_unchecked(1);
+        buf.peek_unchecked();
+        buf.read_unchecked();
+    }
+    buf.write(1).unwrap();
+    buf.peek_back().unwrap();
+    CircularBuffer::balance(&mut buf, &mut CircularBuffer::new(1));
+    buf.write(1).unwrap();
+    buf.peek_mut();
+    buf.front_mut();
+    buf.read().unwrap();
+    buf.diff(&CircularBuffer::new(1));
+    buf.iter();
+    buf.sliding_apply(1, |window| window[0], &mut CircularBuffer::new(4)).unwrap();
+    buf.iter_mut();
+    buf.records(1).count();
+    buf.retain(|_| true);
+    buf.run_length_encode();
+    buf.sum();
+    buf.min_element();
+    buf.max_element();
+    buf.mean();
+    buf.mean_by(|value| *value as f64);
+    CircularBuffer::from_run_length(2, &[(1u8, 2)]).unwrap();
+    buf.write_overwrite(1);
+    buf.write_overwrite_with(1, |_| {});
+    buf.write(1).unwrap();
+    buf.compare_and_replace_front(&1, 2).unwrap();
+    buf.read().unwrap();
+    CircularBuffer::<u8>::new_aligned(4, 16);
+    CircularBuffer::<u8>::try_new(4).unwrap();
+    buf.on_full(Box::new(|| {}));
+    CircularBuffer::<u8>::with_capacity(4);
+    buf.write(1).unwrap();
+    buf.read_slice(1).unwrap();
+    buf.trapezoidal_integral(1.0);
+    buf.rotate_left(1);
+    buf.rotate_right(1);
+    buf.resize_keep_newest(4);
+    buf.truncate(4);
+    buf.fill(0);
+    buf.append(&mut CircularBuffer::new(1)).unwrap();
+    buf.position(|_| true);
+    buf.find(|_| true);
+    buf.windows(1);
+    buf.chunks(1);
+    buf.to_string_with(",", "_");
+    buf.restore(buf.snapshot());
+    buf.get(0);
+    buf.get_mut(0);
+    buf.extend_from_slice_overwrite(&[1]);
+    buf.secure_clear();
+    buf.write(1).unwrap();
+    buf.drain_n(1).unwrap().count();
+    CircularBuffer::<u8>::new_growable(1, None);
+    buf.first();
+    buf.last();
+    buf.write(1).unwrap();
+    buf.swap(0, 0);
+    buf.swap_remove(0);
+    buf.count(|_| true);
+    buf.any(|_| true);
+    buf.all(|_| true);
+    buf.back_nth(0);
+    buf.map_into(|&x| x);
+    buf.write_iter(0..1);
+    buf.write(1).unwrap();
+    buf.read_replace(0).unwrap();
+    buf.as_slices();
+    buf.as_mut_slices();
+    CircularBuffer::<u8>::try_with_capacity(4).unwrap();
+    buf.split_at(0);
+    buf.reset();
+    buf.dedup();
+    buf.dedup_by_key(|&x| x);
+    buf.dedup_by(|a, b| a == b);
+    buf.write(1).unwrap();
+    buf.set(0, 1).unwrap();
+    buf.iter_rev().count();
+    CircularBuffer::<u8>::try_from_slice(4, &[1]).unwrap();
+    buf.extend_overwrite(vec![1]);
+    buf.occupancy();
+    buf.flush_to(&mut Vec::new()).unwrap();
+    buf.fill_from(&mut &[1u8][..]).unwrap();
+    buf.saturating_write(1);
+    buf.dropped_count();
+    buf.reset_dropped_count();
+    buf.binary_search(&1).ok();
+    buf.keep_newest(4);
+    buf.keep_oldest(4);
+    buf.write_partial(&[1]);
+    buf.set_overwrite(false);
+    buf.is_overwriting();
+    buf.indexed_iter().count();
+    buf.clone_empty();
+    buf.split_off(0);
+    buf.peek_chunk(1);
+    buf.peek_cow(0).unwrap();
+    buf.drain_while(|_| false);
+    buf.checked_write_many(&[]).unwrap();
+    buf.as_vec();
+    buf.rposition(|_| false);
+    buf.high_water_mark();
+    buf.reset_high_water_mark();
+    buf.swap_with_slice(&mut vec![0; buf.size()]).unwrap();
+    buf.move_into(&mut CircularBuffer::new(8)).unwrap();
+    buf.peek_range(0..0).unwrap();
+    buf.frequency();
+    buf.is_contiguous();
+    buf.memory_footprint();
+    buf.try_read_exact(0);
+    buf.head_slice();
+    buf.set_overflow_policy(circular_buffer::ErrorPolicy);
+    buf.shift_out(0).unwrap();
+    buf.write_all_iter(Vec::new()).unwrap();
+    buf.available_contiguous_write();
+    buf.write(1).unwrap();
+    buf.read_tap(|_| {}).unwrap();
+    buf.to_vec_from(0);
+    buf.is_sorted();
+    buf.merge_sorted(&CircularBuffer::new(1));
+    buf.as_ptr();
+    buf.as_mut_ptr();
+    buf.fill_from_limited(&mut &[1u8][..], 0).unwrap();
+    buf.starts_with(&[]);
+    buf.ends_with(&[]);
+    buf.clear();
+    buf.write_slice(&[1,2]).unwrap();
     buf.read_many(2).unwrap();
     buf.clear();
+    buf.clear_fast();
+
+    CircularBuffer::<Vec<u8>>::new(1).flatten();
+    CircularBuffer::<u8>::new(1).shrink_to_fit();
+    CircularBuffer::<u8>::new(1).grow_to(2);
+    CircularBuffer::<u8>::new(1).resize_with(2, || 1);
+    CircularBuffer::<u8>::element_size();
+    CircularBuffer::<u8>::from_fn(1, 1, |_| 1).unwrap();
+    CircularBuffer::<u8>::from_vec_deque(1, std::collections::VecDeque::new()).unwrap();
+    CircularBuffer::<u8>::new_overwriting(1);
+    CircularBuffer::<u8>::builder().capacity(1).overwrite(true).fill(0).prefault(true).build().unwrap();
+
+}
+
+#[test]
+// verify that all expected methods of the inline variant are publicly available (not private)
+fn test_check_array_methods() {
+
+    let mut buf = CircularBufferArray::<u8, 4>::new();
+    buf.write(1).unwrap();
+    buf.is_empty();
+    buf.is_full();
+    buf.peek().unwrap();
+    buf.read().unwrap();
+    buf.size();
+    buf.capacity();
+    buf.clear();
 
 }